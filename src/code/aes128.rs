@@ -106,6 +106,43 @@ impl Aes128 {
     pub fn ctr_inplace(&self, nonce: u64, buf: &mut[u8]) {
         Aes128Ctr::inplace(self, nonce, buf);
     }
+
+    /// Inplace encr/decr of `buf` as the part of the stream that starts
+    /// `offset` bytes in
+    pub fn ctr_at(&self, nonce: u64, offset: usize, buf: &mut [u8]) {
+        Aes128Ctr::inplace_at(self, nonce, offset, buf);
+    }
+
+    /// `cipher` with the plaintext from `offset` on replaced by `newtext`,
+    /// without decrypting the rest. Can write past the end, and any gap
+    /// before `offset` is zero-filled.
+    pub fn ctr_edit(
+        &self,
+        nonce: u64,
+        cipher: &[u8],
+        offset: usize,
+        newtext: &[u8],
+    ) -> Vec<u8> {
+        let mut edited = Vec::from(cipher);
+        let end = offset + newtext.len();
+        if end > edited.len() {
+            edited.resize(end, 0);
+        }
+        let part = &mut edited[offset..end];
+        part.copy_from_slice(newtext);
+        self.ctr_at(nonce, offset, part);
+        edited
+    }
+}
+
+/// Challenge 25's edit, with nonce 0
+pub fn edit(
+    cipher: &[u8],
+    key: &[u8],
+    offset: usize,
+    newtext: &[u8],
+) -> Vec<u8> {
+    Aes128::new(key).ctr_edit(0, cipher, offset, newtext)
 }
 
 //pub struct Aes128(aes::Aes128,[u8;ROUNDKEYS_SIZE])
@@ -154,24 +191,38 @@ impl<'a> Aes128Ctr<'a> {
         return Self { aes, state };
     }
 
+    /// Sets the counter to `block`, little endian like `incr`
+    fn seek(&mut self, block: u64) {
+        self.state[BLOCKSIZE / 2..].copy_from_slice(&block.to_le_bytes());
+    }
+
     /// Inplace encrypt/decrypt
     fn inplace(aes: &'a Aes128, nonce: u64, buf: &mut[u8]) {
+        Self::inplace_at(aes, nonce, 0, buf);
+    }
+
+    /// Inplace encrypt/decrypt, `buf` starting `offset` bytes into the
+    /// stream
+    fn inplace_at(
+        aes: &'a Aes128,
+        nonce: u64,
+        offset: usize,
+        buf: &mut [u8],
+    ) {
         use crate::code;
         let mut ctr = Self::new(aes, nonce);
+        ctr.seek((offset / BLOCKSIZE) as u64);
         let mut keystream = [0; BLOCKSIZE];
-        let whole_blocks = buf.len() / BLOCKSIZE;
-        for i in 0..whole_blocks {
-            let blk_beg = i * BLOCKSIZE;
-            let blk_end = blk_beg + BLOCKSIZE;
-            let blk = &mut buf[blk_beg..blk_end];
-            ctr.keystream_block(&mut keystream);
-            code::block_xor(blk, &keystream);
-        }
-        let remaining_len = buf.len() % BLOCKSIZE;
-        if remaining_len > 0 {
-            let blk = code::lastn_mut(buf, remaining_len);
+        // Where in the first keystream block `buf` starts
+        let mut skip = offset % BLOCKSIZE;
+        let mut pos = 0;
+        while pos < buf.len() {
             ctr.keystream_block(&mut keystream);
-            code::block_xor(blk, &keystream[..remaining_len]);
+            let n = (BLOCKSIZE - skip).min(buf.len() - pos);
+            let ks = &keystream[skip..skip + n];
+            code::block_xor(&mut buf[pos..pos + n], ks);
+            pos += n;
+            skip = 0;
         }
     }
 
@@ -0,0 +1,270 @@
+//! Statistics for telling what an unknown ciphertext might be.
+//!
+//! Good ciphertext looks uniformly random: about 8 bits of entropy per
+//! byte, chi-squared near 255 and index of coincidence near 1/256. ECB
+//! shows through repeated blocks and xor with a short key through repeated
+//! n-grams.
+
+use std::collections::HashMap;
+use std::fmt;
+
+pub fn histogram(buf: &[u8]) -> [usize; 256] {
+    let mut counts = [0; 256];
+    buf.iter().for_each(|b| counts[*b as usize] += 1);
+    counts
+}
+
+fn entropy_of(counts: &[usize; 256], total: usize) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    let total = total as f64;
+    counts
+        .iter()
+        .filter(|c| **c > 0)
+        .map(|c| {
+            let p = *c as f64 / total;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Shannon entropy in bits per byte, 0 to 8
+pub fn entropy(buf: &[u8]) -> f64 {
+    entropy_of(&histogram(buf), buf.len())
+}
+
+/// Entropy of every whole block.
+/// OBS: a 16 byte block can't have more than 4 bits per byte.
+pub fn entropy_blocks(buf: &[u8], blocksize: usize) -> Vec<f64> {
+    buf.chunks_exact(blocksize).map(entropy).collect()
+}
+
+/// Entropy of every `window` long slice, sliding one byte at a time
+pub fn entropy_windows(buf: &[u8], window: usize) -> Vec<f64> {
+    if window == 0 || buf.len() < window {
+        return Vec::new();
+    }
+    let mut counts = histogram(&buf[..window]);
+    let mut out = vec![entropy_of(&counts, window)];
+    for i in window..buf.len() {
+        counts[buf[i - window] as usize] -= 1;
+        counts[buf[i] as usize] += 1;
+        out.push(entropy_of(&counts, window));
+    }
+    out
+}
+
+/// Chi-squared of the byte counts against uniform.
+/// Uniform data has mean 255 and standard deviation about 22.6,
+/// 0 for an empty buffer.
+pub fn chi_squared(buf: &[u8]) -> f64 {
+    if buf.is_empty() {
+        return 0.0;
+    }
+    let expected = buf.len() as f64 / 256.0;
+    histogram(buf)
+        .iter()
+        .map(|c| {
+            let d = *c as f64 - expected;
+            d * d / expected
+        })
+        .sum()
+}
+
+/// Probability that two bytes at random positions are equal.
+/// Uniform is 1/256, English text around 0.06.
+pub fn index_of_coincidence(buf: &[u8]) -> f64 {
+    let n = buf.len();
+    if n < 2 {
+        return 0.0;
+    }
+    let same: usize =
+        histogram(buf).iter().map(|c| c * c.saturating_sub(1)).sum();
+    same as f64 / (n * (n - 1)) as f64
+}
+
+/// Every n-gram that occurs more than once, with its count, most common
+/// first
+pub fn repeated_ngrams(buf: &[u8], n: usize) -> Vec<(&[u8], usize)> {
+    let mut counts: HashMap<&[u8], usize> = HashMap::new();
+    for w in buf.windows(n) {
+        *counts.entry(w).or_insert(0) += 1;
+    }
+    let mut reps: Vec<_> =
+        counts.into_iter().filter(|(_, c)| *c > 1).collect();
+    reps.sort_by(|(a, x), (b, y)| y.cmp(x).then(a.cmp(b)));
+    reps
+}
+
+/// (n, number of distinct repeated n-grams) for n from 2 up to `max_n`.
+/// Random data has barely any past n=3.
+pub fn ngram_spectrum(buf: &[u8], max_n: usize) -> Vec<(usize, usize)> {
+    (2..=max_n)
+        .map(|n| (n, repeated_ngrams(buf, n).len()))
+        .collect()
+}
+
+/// Number of whole blocks equal to an earlier block
+pub fn repeated_blocks(buf: &[u8], blocksize: usize) -> usize {
+    let blocks: Vec<_> = buf.chunks_exact(blocksize).collect();
+    let mut unique = blocks.clone();
+    unique.sort();
+    unique.dedup();
+    blocks.len() - unique.len()
+}
+
+pub fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Greatest common divisor of ciphertext lengths. With enough different
+/// plaintext lengths this is the block size.
+pub fn block_size_gcd(lens: &[usize]) -> usize {
+    lens.iter().fold(0, |acc, l| gcd(acc, *l))
+}
+
+/// Block sizes `Report` looks for repeats with
+pub const BLOCKSIZES: &[usize] = &[8, 16, 32];
+
+pub struct Report {
+    pub len: usize,
+    pub entropy: f64,
+    pub chi_squared: f64,
+    pub ioc: f64,
+    /// Part of bytes that are printable ascii or whitespace
+    pub printable: f64,
+    /// (blocksize, repeated blocks)
+    pub repeats: Vec<(usize, usize)>,
+    pub spectrum: Vec<(usize, usize)>,
+    /// Most common repeated 3-grams
+    pub top_trigrams: Vec<(Vec<u8>, usize)>,
+}
+
+impl Report {
+    pub fn new(buf: &[u8]) -> Self {
+        let text = buf
+            .iter()
+            .filter(|b| b.is_ascii_graphic() || b.is_ascii_whitespace())
+            .count();
+        Self {
+            len: buf.len(),
+            entropy: entropy(buf),
+            chi_squared: chi_squared(buf),
+            ioc: index_of_coincidence(buf),
+            printable: text as f64 / buf.len().max(1) as f64,
+            repeats: BLOCKSIZES
+                .iter()
+                .map(|bs| (*bs, repeated_blocks(buf, *bs)))
+                .collect(),
+            spectrum: ngram_spectrum(buf, 8),
+            top_trigrams: repeated_ngrams(buf, 3)
+                .into_iter()
+                .take(5)
+                .map(|(g, c)| (Vec::from(g), c))
+                .collect(),
+        }
+    }
+
+    /// Uniform within 4 standard deviations of chi-squared
+    pub fn looks_uniform(&self) -> bool {
+        self.chi_squared < 255.0 + 4.0 * 22.6
+    }
+
+    /// Largest block size whose repeats cover as many bytes as with the
+    /// smallest one. ECB with 16 byte blocks also repeats 8 byte blocks,
+    /// but not always 32 byte ones.
+    pub fn likely_ecb(&self) -> Option<usize> {
+        let mut reps = self.repeats.iter().skip_while(|(_, r)| *r == 0);
+        let (mut best, r) = *reps.next()?;
+        let bytes = best * r;
+        for (bs, r) in reps {
+            if bs * r != bytes {
+                break;
+            }
+            best = *bs;
+        }
+        Some(best)
+    }
+
+    pub fn guesses(&self) -> Vec<String> {
+        let mut out = Vec::new();
+        if self.printable > 0.95 {
+            out.push(String::from("Text, not encrypted or encoded"));
+        } else if self.looks_uniform() {
+            out.push(String::from("Uniform, a good cipher or random"));
+        } else {
+            out.push(String::from("Not uniform, weak cipher or plain data"));
+        }
+        // Text repeats blocks all the time
+        if let Some(bs) = self.likely_ecb().filter(|_| self.printable <= 0.95)
+        {
+            out.push(format!("Repeated {} byte blocks, maybe ECB", bs));
+        }
+        if self.len.is_multiple_of(16) && self.len > 0 {
+            out.push(String::from("Whole 16 byte blocks"));
+        }
+        out
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Length:      {}", self.len)?;
+        writeln!(f, "Entropy:     {:.3} bits/byte", self.entropy)?;
+        writeln!(f, "Chi-squared: {:.1} (uniform ~255)", self.chi_squared)?;
+        let uniform = 1.0 / 256.0;
+        writeln!(f, "IoC:         {:.5} (uniform {:.5})", self.ioc, uniform)?;
+        writeln!(f, "Printable:   {:.1}%", 100.0 * self.printable)?;
+        for (bs, r) in &self.repeats {
+            writeln!(f, "Repeated {:2} byte blocks: {}", bs, r)?;
+        }
+        let spectrum: Vec<_> = self
+            .spectrum
+            .iter()
+            .map(|(n, c)| format!("{}:{}", n, c))
+            .collect();
+        writeln!(f, "Repeated n-grams: {}", spectrum.join(" "))?;
+        for (g, c) in &self.top_trigrams {
+            let hex = crate::code::encode_hex(g);
+            writeln!(f, "  {} x{}", hex, c)?;
+        }
+        for g in self.guesses() {
+            writeln!(f, "-> {}", g)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_stats() {
+    use rand::{Rng, SeedableRng};
+    let mut rng = rand::rngs::StdRng::seed_from_u64(39);
+    let random: Vec<u8> = (0..1 << 16).map(|_| rng.gen()).collect();
+    assert!(entropy(&random) > 7.99);
+    assert!(Report::new(&random).looks_uniform());
+    assert!((index_of_coincidence(&random) - 1.0 / 256.0).abs() < 1e-4);
+    assert_eq!(entropy(&[7; 100]), 0.0);
+    assert_eq!(index_of_coincidence(&[7; 100]), 1.0);
+
+    let windows = entropy_windows(b"aaaabbbb", 4);
+    let h = 0.8112781244591328;
+    assert_eq!(windows, vec![0.0, h, 1.0, h, 0.0]);
+
+    let (a, b) = (&random[..16], &random[16..32]);
+    let ecb = [a, b, a].concat();
+    assert_eq!(repeated_blocks(&ecb, 16), 1);
+    assert_eq!(Report::new(&ecb).likely_ecb(), Some(16));
+    assert_eq!(repeated_ngrams(b"abcabcab", 3)[0], (&b"abc"[..], 2));
+    assert_eq!(block_size_gcd(&[48, 32, 64, 80]), 16);
+
+    // '.' is text, not hexdump's placeholder
+    let text = Report::new(b"Dots... Lots of dots. End.\n");
+    assert_eq!(text.printable, 1.0);
+    assert_eq!(Report::new(b"ab\x00\xff").printable, 0.5);
+    assert_eq!(chi_squared(&[]), 0.0);
+}
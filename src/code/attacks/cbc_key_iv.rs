@@ -0,0 +1,45 @@
+//! Getting the key out of CBC that uses it as iv, challenge 27.
+//!
+//! Decrypting C1 || 0 || C1 gives P1' = D(C1) ^ key and P3' = D(C1) ^ 0,
+//! so P1' ^ P3' is the key. The receiver only has to show the plaintext,
+//! eg in an error message.
+
+use crate::code;
+use code::aes128::BLOCKSIZE;
+use code::oracles::HighAscii;
+
+/// Key from any ciphertext of at least 3 blocks, `decrypt` complaining
+/// with the plaintext when it isn't ascii.
+/// None if the modified message happened to decrypt to ascii.
+pub fn recover_key<F>(cipher: &[u8], decrypt: F) -> Option<Vec<u8>>
+where
+    F: Fn(&[u8]) -> Result<(), HighAscii>,
+{
+    assert!(cipher.len() >= 3 * BLOCKSIZE);
+    let c1 = &cipher[..BLOCKSIZE];
+    // The rest stays, so any padding is still right
+    let mut modified = c1.to_vec();
+    modified.extend(&[0; BLOCKSIZE]);
+    modified.extend(c1);
+    modified.extend(&cipher[3 * BLOCKSIZE..]);
+    let plain = decrypt(&modified).err()?.plain;
+    let mut key = plain[..BLOCKSIZE].to_vec();
+    code::block_xor(&mut key, &plain[2 * BLOCKSIZE..3 * BLOCKSIZE]);
+    Some(key)
+}
+
+#[test]
+fn test_recover_key() {
+    use code::aes128::Aes128;
+    use code::oracles::CbcKeyIvOracle;
+
+    let oracle = CbcKeyIvOracle::new();
+    let plain = b"comment1=cooking%20MCs;userdata=foo;comment2=%20like%20a";
+    let cipher = oracle.encrypt(plain);
+    assert!(oracle.decrypt(&cipher).is_ok());
+
+    let key = recover_key(&cipher, |c| oracle.decrypt(c)).unwrap();
+    let mut buf = cipher.clone();
+    Aes128::new(&key).cbc_decr(&key, &mut buf);
+    assert_eq!(code::pkcs7_validate(&buf), Some(&plain[..]));
+}
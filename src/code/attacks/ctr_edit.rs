@@ -0,0 +1,67 @@
+//! Reading CTR encrypted data through an edit function, challenge 25.
+//!
+//! Writing known plaintext over the ciphertext gives the keystream at
+//! that spot, and the keystream xor the original ciphertext is the
+//! plaintext.
+
+use crate::code;
+
+/// Plaintext of `cipher`, where `edit(cipher, offset, newtext)` returns
+/// `cipher` with the plaintext from `offset` replaced. Writes `chunk`
+/// bytes at a time, like a disk writes sectors.
+pub fn recover<F>(cipher: &[u8], chunk: usize, edit: F) -> Vec<u8>
+where
+    F: Fn(&[u8], usize, &[u8]) -> Vec<u8>,
+{
+    assert!(chunk > 0);
+    let zeros = vec![0; chunk];
+    let mut plain = Vec::from(cipher);
+    for offset in (0..cipher.len()).step_by(chunk) {
+        let n = chunk.min(cipher.len() - offset);
+        // Zeros encrypt to the keystream itself
+        let edited = edit(cipher, offset, &zeros[..n]);
+        code::block_xor(
+            &mut plain[offset..offset + n],
+            &edited[offset..offset + n],
+        );
+    }
+    plain
+}
+
+#[test]
+fn test_recover() {
+    use code::aes128::Aes128;
+    use code::oracles::CtrEditOracle;
+
+    // Long enough for the counter to carry into its second byte
+    let plain: Vec<u8> = (0..5000).map(|i| (i * 7 % 251) as u8).collect();
+    let oracle = CtrEditOracle::new(&plain);
+    let cipher = oracle.cipher();
+    let edit = |c: &[u8], o, n: &[u8]| oracle.edit(c, o, n);
+    assert_eq!(recover(&cipher, 512, edit), plain);
+    assert_eq!(oracle.queries(), 10);
+
+    // Same as encrypting the edited plaintext from the start
+    let aes = Aes128::new(b"YELLOW SUBMARINE");
+    let encrypt = |p: &[u8]| {
+        let mut buf = Vec::from(p);
+        aes.ctr_inplace(7, &mut buf);
+        buf
+    };
+    let full = encrypt(&plain);
+    for (offset, newtext) in [(4100, &b"seeking write"[..]), (4990, &[1; 20])] {
+        let mut expected = plain[..offset].to_vec();
+        expected.extend(newtext);
+        expected.extend(plain.iter().skip(expected.len()));
+        let edited = aes.ctr_edit(7, &full, offset, newtext);
+        assert_eq!(edited, encrypt(&expected));
+    }
+
+    // Seeking past the end leaves zeros in between
+    let edited = aes.ctr_edit(7, &full, 5010, b"tail");
+    assert_eq!(&edited[..5000], &full[..]);
+    assert_eq!(&edited[5000..5010], &[0; 10]);
+    let mut tail = vec![0; 5010];
+    tail.extend(b"tail");
+    assert_eq!(&edited[5010..], &encrypt(&tail)[5010..]);
+}
@@ -0,0 +1,140 @@
+//! Reading the DH echo protocol from the middle, challenges 34 and 35.
+//!
+//! Neither side checks what it is sent, so the middle can force a shared
+//! secret it knows. Key fixing swaps both public keys for p, making the
+//! secret 0. Changing g during negotiation does the same with g = 1
+//! (secret 1), g = p (secret 0) or g = p - 1 (secret 1 or p - 1).
+
+use crate::code;
+use code::bigint::BigUint;
+use code::dh::{self, Group};
+use code::protocol::dh_echo::Msg;
+use code::protocol::{Mitm, Side};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Attack {
+    /// Replace A and B with p
+    KeyFixing,
+    /// Replace g with 1 in the negotiation
+    GOne,
+    /// Replace g with p
+    GP,
+    /// Replace g with p - 1
+    GPMinusOne,
+}
+
+/// Forwards everything after tampering with the handshake, and decrypts
+/// the data both ways
+pub struct DhMitm {
+    attack: Attack,
+    p: Option<BigUint>,
+    /// A and B as sent
+    publics: Vec<BigUint>,
+    /// Plaintexts read, with who sent them
+    pub recovered: Vec<(Side, Vec<u8>)>,
+}
+
+impl DhMitm {
+    pub fn new(attack: Attack) -> Self {
+        Self {
+            attack,
+            p: None,
+            publics: Vec::new(),
+            recovered: Vec::new(),
+        }
+    }
+
+    /// The group with g replaced, if that is the attack
+    fn tamper(&mut self, mut group: Group) -> Group {
+        let one = BigUint::one();
+        self.p = Some(group.p.clone());
+        match self.attack {
+            Attack::KeyFixing => (),
+            Attack::GOne => group.g = one,
+            Attack::GP => group.g = group.p.clone(),
+            Attack::GPMinusOne => group.g = &group.p - &one,
+        }
+        group
+    }
+
+    /// The shared secret both sides ended up with
+    fn secret(&self) -> Option<BigUint> {
+        let p = self.p.as_ref()?;
+        let one = BigUint::one();
+        match self.attack {
+            Attack::KeyFixing | Attack::GP => Some(BigUint::zero()),
+            Attack::GOne => Some(one),
+            // A and B are each 1 or p - 1, and so is the secret. It is
+            // p - 1 only if both exponents, so both public keys, are odd.
+            Attack::GPMinusOne => {
+                let minus_one = p - &one;
+                match self.publics.as_slice() {
+                    [a, b] if *a == minus_one && *b == minus_one => {
+                        Some(minus_one)
+                    }
+                    _ => Some(one),
+                }
+            }
+        }
+    }
+
+    fn read(&self, cipher: &[u8]) -> Option<Vec<u8>> {
+        dh::decrypt(&dh::session_key(&self.secret()?), cipher)
+    }
+}
+
+impl Mitm<Msg> for DhMitm {
+    fn intercept(&mut self, from: Side, msg: Msg) -> Vec<Msg> {
+        let fixing = self.attack == Attack::KeyFixing;
+        let msg = match msg {
+            Msg::Hello { group, public } => {
+                self.publics.push(public.clone());
+                let group = self.tamper(group);
+                let public = if fixing { group.p.clone() } else { public };
+                Msg::Hello { group, public }
+            }
+            Msg::Negotiate(group) => Msg::Negotiate(self.tamper(group)),
+            Msg::Ack(group) => Msg::Ack(self.tamper(group)),
+            Msg::Public(public) => {
+                self.publics.push(public.clone());
+                match &self.p {
+                    Some(p) if fixing => Msg::Public(p.clone()),
+                    _ => Msg::Public(public),
+                }
+            }
+            Msg::Data(cipher) => {
+                if let Some(plain) = self.read(&cipher) {
+                    self.recovered.push((from, plain));
+                }
+                Msg::Data(cipher)
+            }
+        };
+        vec![msg]
+    }
+}
+
+#[test]
+fn test_dh_mitm() {
+    use code::protocol::dh_echo::{client, echo_bot, Handshake};
+    use code::protocol::run;
+
+    let group = Group::default();
+    let messages: &[&[u8]] = &[b"attack at dawn", b"bring snacks"];
+    let cases = [
+        (Attack::KeyFixing, Handshake::Combined),
+        (Attack::KeyFixing, Handshake::Negotiated),
+        (Attack::GOne, Handshake::Negotiated),
+        (Attack::GP, Handshake::Negotiated),
+        (Attack::GPMinusOne, Handshake::Negotiated),
+    ];
+    for (attack, handshake) in &cases {
+        let mut mitm = DhMitm::new(*attack);
+        let client = |ep| client(ep, &group, *handshake, messages);
+        let (echoes, received, _) = run(client, echo_bot, &mut mitm);
+        // Neither side notices
+        assert_eq!(echoes.unwrap(), messages);
+        assert_eq!(received.unwrap(), messages);
+        let read: Vec<_> = mitm.recovered.iter().map(|(_, m)| m).collect();
+        assert_eq!(read, [messages[0], messages[0], messages[1], messages[1]]);
+    }
+}
@@ -0,0 +1,10 @@
+//! Attacks general enough to be used outside a single challenge.
+//!
+//! They work on the oracles in `code::oracles`, or anything else answering
+//! the same way.
+
+pub mod cbc_key_iv;
+pub mod ctr_edit;
+pub mod dh_mitm;
+pub mod srp;
+pub mod timing;
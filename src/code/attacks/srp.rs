@@ -0,0 +1,132 @@
+//! Breaking SRP, challenges 37 and 38.
+//!
+//! A client that sends A = 0, N, 2N, .. makes the server's
+//! `S = (A v^u)^b` zero, so it can log in knowing only that. A server
+//! playing simplified SRP with b = 1 and u = 1 gets a proof of
+//! `S = A g^x`, which can be checked against password guesses offline.
+
+use crate::code;
+use code::bigint::BigUint;
+use code::dh::Group;
+use code::hash::hmac::constant_time_eq;
+use code::protocol::srp::{self, Error, Msg};
+use code::protocol::Endpoint;
+
+/// Logs in as `user` without the password by sending `multiple * N` as A,
+/// to a server that doesn't check it
+pub fn zero_key_login(
+    mut ep: Endpoint<Msg>,
+    group: &Group,
+    user: &str,
+    multiple: u64,
+) -> Result<(), Error> {
+    let public = &group.p * &BigUint::from(multiple);
+    ep.send(Msg::Hello {
+        user: user.to_string(),
+        public,
+    });
+    let salt = match ep.recv()? {
+        Msg::Challenge { salt, .. } => salt,
+        Msg::Verdict(false) => return Err(Error::Rejected),
+        other => return Err(Error::Unexpected(other)),
+    };
+    ep.send(Msg::Proof(srp::proof(&BigUint::zero(), &salt)));
+    match ep.recv()? {
+        Msg::Verdict(true) => Ok(()),
+        Msg::Verdict(false) => Err(Error::Rejected),
+        other => Err(Error::Unexpected(other)),
+    }
+}
+
+/// What a fake server gets out of a simplified SRP client
+#[derive(Clone, Debug)]
+pub struct Captured {
+    pub user: String,
+    pub public: BigUint,
+    pub salt: Vec<u8>,
+    pub proof: Vec<u8>,
+}
+
+/// Plays a simplified SRP server with b = 1, u = 1 and no salt, and
+/// says yes to whatever proof it gets
+pub fn fake_server(
+    mut ep: Endpoint<Msg>,
+    group: &Group,
+) -> Result<Captured, Error> {
+    let (user, public) = match ep.recv()? {
+        Msg::Hello { user, public } => (user, public),
+        other => return Err(Error::Unexpected(other)),
+    };
+    let salt = Vec::new();
+    ep.send(Msg::Challenge {
+        salt: salt.clone(),
+        public: group.g.clone(),
+        u: Some(BigUint::one()),
+    });
+    let proof = match ep.recv()? {
+        Msg::Proof(proof) => proof,
+        other => return Err(Error::Unexpected(other)),
+    };
+    ep.send(Msg::Verdict(true));
+    Ok(Captured {
+        user,
+        public,
+        salt,
+        proof,
+    })
+}
+
+/// The password in `dictionary` that gives the captured proof, trying
+/// S = A g^x for each
+pub fn crack<'a, I>(
+    group: &Group,
+    captured: &Captured,
+    dictionary: I,
+) -> Option<&'a str>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let Captured {
+        user,
+        public,
+        salt,
+        proof,
+    } = captured;
+    dictionary.into_iter().find(|password| {
+        let x = srp::private_key(salt, user, password);
+        let s = &(public * &group.g.modpow(&x, &group.p)) % &group.p;
+        constant_time_eq(&srp::proof(&s, salt), proof)
+    })
+}
+
+#[test]
+fn test_srp_attacks() {
+    use code::protocol::srp::{client, Server, Variant};
+    use code::protocol::{run, Passive};
+
+    let group = Group::default();
+    let mut server = Server::new(group.clone(), Variant::Srp6a);
+    let mut weak =
+        Server::new(group.clone(), Variant::Srp6a).with_public_check(false);
+    server.register("alice", "correct horse battery staple");
+    weak.register("alice", "correct horse battery staple");
+    for multiple in 0..3 {
+        let attacker = |ep| zero_key_login(ep, &group, "alice", multiple);
+        let (res, _, _) = run(attacker, |ep| server.serve(ep), &mut Passive);
+        assert_eq!(res, Err(Error::Rejected));
+        let (res, user, _) = run(attacker, |ep| weak.serve(ep), &mut Passive);
+        assert_eq!(res, Ok(()));
+        assert_eq!(user, Ok("alice".to_string()));
+    }
+
+    let dictionary = ["123456", "password", "letmein", "dragon", "monkey"];
+    let login = |ep| client(ep, &group, Variant::Simplified, "bob", "dragon");
+    let (res, captured, _) =
+        run(login, |ep| fake_server(ep, &group), &mut Passive);
+    assert_eq!(res, Ok(()));
+    let captured = captured.unwrap();
+    let words = dictionary.iter().cloned();
+    assert_eq!(crack(&group, &captured, words), Some("dragon"));
+    let words = dictionary[..3].iter().cloned();
+    assert_eq!(crack(&group, &captured, words), None);
+}
@@ -0,0 +1,310 @@
+//! Recovering a MAC from how long it takes to be rejected, challenges 31
+//! and 32.
+//!
+//! The comparison stops at the first wrong byte, so the right value for
+//! the next byte takes a little longer than the 255 wrong ones. That can
+//! be far less than the noise of a single request. Every guess is timed
+//! repeatedly and summarised with a median or trimmed mean, taking more
+//! samples until the best guess stands out from the rest.
+
+use crate::code;
+use code::encoding::percent;
+use code::oracles::TimingOracle;
+use code::time::TimeSource;
+
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::time::Instant;
+
+/// Somewhere to send signatures and time the answer
+pub trait Target {
+    /// Whether `signature` was accepted, and how many µs that took
+    fn time(&mut self, signature: &[u8]) -> io::Result<(bool, f64)>;
+}
+
+/// A `TimingOracle` called directly, timed with its own clock
+pub struct Direct<'a, C: TimeSource> {
+    oracle: &'a TimingOracle<C>,
+    file: Vec<u8>,
+}
+
+impl<'a, C: TimeSource> Direct<'a, C> {
+    pub fn new(oracle: &'a TimingOracle<C>, file: &[u8]) -> Self {
+        Self {
+            oracle,
+            file: Vec::from(file),
+        }
+    }
+}
+
+impl<C: TimeSource> Target for Direct<'_, C> {
+    fn time(&mut self, signature: &[u8]) -> io::Result<(bool, f64)> {
+        let clock = self.oracle.clock();
+        let start = clock.now_us();
+        let ok = self.oracle.check(&self.file, signature);
+        Ok((ok, (clock.now_us() - start) as f64))
+    }
+}
+
+/// A `TimingServer`, or anything answering `GET /test?file=..&signature=..`
+/// with 200 when right. Timed with the real clock.
+pub struct Http {
+    addr: SocketAddr,
+    file: String,
+}
+
+impl Http {
+    pub fn new(addr: SocketAddr, file: &[u8]) -> Self {
+        Self {
+            addr,
+            file: percent::encode_component(file),
+        }
+    }
+}
+
+impl Target for Http {
+    fn time(&mut self, signature: &[u8]) -> io::Result<(bool, f64)> {
+        let mut stream = TcpStream::connect(self.addr)?;
+        stream.set_nodelay(true)?;
+        let request = format!(
+            "GET /test?file={}&signature={} HTTP/1.1\r\nHost: {}\r\n\r\n",
+            self.file,
+            code::encode_hex(signature),
+            self.addr
+        );
+        // Connecting is not part of the leak
+        let start = Instant::now();
+        stream.write_all(request.as_bytes())?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response)?;
+        let elapsed = start.elapsed().as_secs_f64() * 1e6;
+        Ok((response.starts_with("HTTP/1.1 200"), elapsed))
+    }
+}
+
+/// How the samples of one guess are summarised
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Estimator {
+    Median,
+    /// Mean without this fraction of the samples at each end
+    TrimmedMean(f64),
+}
+
+impl Estimator {
+    pub fn estimate(self, samples: &[f64]) -> f64 {
+        assert!(!samples.is_empty());
+        let mut sorted = Vec::from(samples);
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let n = sorted.len();
+        match self {
+            Estimator::Median if n % 2 == 0 => {
+                (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0
+            }
+            Estimator::Median => sorted[n / 2],
+            Estimator::TrimmedMean(frac) => {
+                let cut = ((n as f64 * frac) as usize).min((n - 1) / 2);
+                let kept = &sorted[cut..n - cut];
+                kept.iter().sum::<f64>() / kept.len() as f64
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ByteReport {
+    pub index: usize,
+    pub byte: u8,
+    /// Lead of the best guess over the runner-up, in standard deviations
+    /// of the other guesses. Infinite if the signature was accepted.
+    pub confidence: f64,
+    /// Lead of the best guess over the runner-up in µs
+    pub margin_us: f64,
+    /// Samples per guess it took
+    pub samples: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct Recovery {
+    pub mac: Vec<u8>,
+    pub bytes: Vec<ByteReport>,
+    /// Whether the target accepted `mac`
+    pub valid: bool,
+    pub queries: usize,
+    /// Times a byte was found to be wrong and done again
+    pub backtracks: usize,
+}
+
+pub struct TimingAttack {
+    len: usize,
+    estimator: Estimator,
+    min_samples: usize,
+    max_samples: usize,
+    threshold: f64,
+}
+
+impl TimingAttack {
+    /// Recovers a `len` byte MAC
+    pub fn new(len: usize) -> Self {
+        assert!(len > 0);
+        Self {
+            len,
+            estimator: Estimator::TrimmedMean(0.25),
+            min_samples: 5,
+            max_samples: 256,
+            threshold: 4.0,
+        }
+    }
+
+    pub fn with_estimator(mut self, estimator: Estimator) -> Self {
+        self.estimator = estimator;
+        self
+    }
+
+    /// Samples per guess start at `min` and double up to `max`
+    pub fn with_samples(mut self, min: usize, max: usize) -> Self {
+        assert!(0 < min && min <= max);
+        self.min_samples = min;
+        self.max_samples = max;
+        self
+    }
+
+    /// Confidence to settle for before `max` samples
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// (best guess, confidence, margin)
+    fn rank(stats: &[f64]) -> (u8, f64, f64) {
+        let best = (0..256)
+            .max_by(|a, b| stats[*a].partial_cmp(&stats[*b]).unwrap())
+            .unwrap();
+        let others: Vec<_> =
+            (0..256).filter(|i| *i != best).map(|i| stats[i]).collect();
+        let runner_up = others.iter().cloned().fold(f64::MIN, f64::max);
+        let mean = others.iter().sum::<f64>() / others.len() as f64;
+        let var = others.iter().map(|x| (x - mean).powi(2)).sum::<f64>()
+            / (others.len() - 1) as f64;
+        let margin = stats[best] - runner_up;
+        let confidence = match (margin > 0.0, var > 0.0) {
+            (false, _) => 0.0,
+            (true, false) => f64::INFINITY,
+            (true, true) => margin / var.sqrt(),
+        };
+        (best as u8, confidence, margin)
+    }
+
+    /// Times every value of `guess[index]` until one stands out. The bool
+    /// is whether the target accepted it.
+    fn recover_byte<T: Target>(
+        &self,
+        target: &mut T,
+        guess: &mut [u8],
+        index: usize,
+        queries: &mut usize,
+    ) -> io::Result<(ByteReport, bool)> {
+        let mut times = vec![Vec::new(); 256];
+        // The last byte is found by being accepted, not by timing
+        let last = index == guess.len() - 1;
+        let mut samples = if last { 1 } else { self.min_samples };
+        loop {
+            // Round robin, so drift hits every guess alike
+            while times[0].len() < samples {
+                for (c, ts) in times.iter_mut().enumerate() {
+                    guess[index] = c as u8;
+                    let (ok, t) = target.time(guess)?;
+                    *queries += 1;
+                    if ok {
+                        let report = ByteReport {
+                            index,
+                            byte: c as u8,
+                            confidence: f64::INFINITY,
+                            margin_us: 0.0,
+                            samples: ts.len() + 1,
+                        };
+                        return Ok((report, true));
+                    }
+                    ts.push(t);
+                }
+            }
+            let stats: Vec<_> =
+                times.iter().map(|ts| self.estimator.estimate(ts)).collect();
+            let (byte, confidence, margin_us) = Self::rank(&stats);
+            let done = confidence >= self.threshold;
+            if last || done || samples >= self.max_samples {
+                let report = ByteReport {
+                    index,
+                    byte,
+                    confidence,
+                    margin_us,
+                    samples,
+                };
+                return Ok((report, false));
+            }
+            samples = (2 * samples).min(self.max_samples);
+        }
+    }
+
+    /// Calls `progress` after every recovered byte.
+    /// A byte that doesn't stand out even with `max` samples means the one
+    /// before it was probably wrong, so that one is done again. At most
+    /// `len` times in all.
+    pub fn run<T, F>(
+        &self,
+        target: &mut T,
+        mut progress: F,
+    ) -> io::Result<Recovery>
+    where
+        T: Target,
+        F: FnMut(&ByteReport),
+    {
+        let mut guess = vec![0; self.len];
+        let mut bytes: Vec<ByteReport> = Vec::with_capacity(self.len);
+        let mut queries = 0;
+        let mut backtracks = 0;
+        let mut valid = false;
+        while !valid && bytes.len() < self.len {
+            let index = bytes.len();
+            let (report, ok) =
+                self.recover_byte(target, &mut guess, index, &mut queries)?;
+            // All but the last byte should have been accepted at the end
+            let lost = !ok
+                && (report.confidence < self.threshold
+                    || index == self.len - 1);
+            if lost && index > 0 && backtracks < self.len {
+                backtracks += 1;
+                bytes.pop();
+                continue;
+            }
+            valid = ok;
+            guess[index] = report.byte;
+            progress(&report);
+            bytes.push(report);
+        }
+        Ok(Recovery {
+            mac: guess,
+            bytes,
+            valid,
+            queries,
+            backtracks,
+        })
+    }
+}
+
+#[test]
+fn test_recover_hmac() {
+    use code::time::SimClock;
+
+    let oracle = TimingOracle::new(SimClock::at_ms(0), 50).with_jitter(100, 44);
+    let attack = TimingAttack::new(code::hash::sha1::OUTPUT);
+    let res = attack
+        .run(&mut Direct::new(&oracle, b"foo"), |_| ())
+        .unwrap();
+    assert!(res.valid);
+    assert_eq!(res.mac, oracle.expected(b"foo"));
+    assert_eq!(res.queries, oracle.queries());
+
+    assert_eq!(Estimator::Median.estimate(&[5.0, 1.0, 3.0, 100.0]), 4.0);
+    let trimmed = Estimator::TrimmedMean(0.25).estimate(&[1.0, 2.0, 3.0, 90.0]);
+    assert_eq!(trimmed, 2.5);
+}
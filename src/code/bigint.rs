@@ -0,0 +1,455 @@
+//! Arbitrary size unsigned integers, enough for Diffie-Hellman and SRP.
+//!
+//! Little endian u64 limbs without trailing zero limbs, so every number
+//! has one representation. Schoolbook multiplication and Knuth's
+//! algorithm D for division.
+//! OBS: nothing here is constant time.
+
+use crate::code::rng;
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Rem, Sub};
+
+#[derive(Clone, PartialEq, Eq, Hash, Default)]
+pub struct BigUint {
+    limbs: Vec<u64>,
+}
+
+impl BigUint {
+    fn from_limbs(mut limbs: Vec<u64>) -> Self {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        Self { limbs }
+    }
+
+    pub fn zero() -> Self {
+        Self::default()
+    }
+
+    pub fn one() -> Self {
+        Self::from(1)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    /// Number of bits without leading zeros, 0 for 0
+    pub fn bits(&self) -> usize {
+        match self.limbs.last() {
+            None => 0,
+            Some(top) => 64 * self.limbs.len() - top.leading_zeros() as usize,
+        }
+    }
+
+    /// Bit `i`, counting from the least significant
+    pub fn bit(&self, i: usize) -> bool {
+        self.limbs
+            .get(i / 64)
+            .is_some_and(|l| (l >> (i % 64)) & 1 == 1)
+    }
+
+    pub fn from_bytes_be(bytes: &[u8]) -> Self {
+        let limbs = bytes
+            .rchunks(8)
+            .map(|c| c.iter().fold(0, |acc, b| (acc << 8) | *b as u64))
+            .collect();
+        Self::from_limbs(limbs)
+    }
+
+    /// Big endian without leading zeros, `[0]` for 0
+    pub fn to_bytes_be(&self) -> Vec<u8> {
+        let len = self.bits().div_ceil(8).max(1);
+        self.to_bytes_be_padded(len)
+    }
+
+    /// Big endian, left padded with zeros to `len` bytes. Panics if it
+    /// doesn't fit.
+    pub fn to_bytes_be_padded(&self, len: usize) -> Vec<u8> {
+        assert!(self.bits() <= 8 * len, "Doesn't fit in {} bytes", len);
+        let mut out = vec![0; len];
+        for (i, b) in out.iter_mut().rev().enumerate() {
+            if let Some(l) = self.limbs.get(i / 8) {
+                *b = (l >> (8 * (i % 8))) as u8;
+            }
+        }
+        out
+    }
+
+    /// Whitespace is ignored, so constants can be copied from RFCs
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let digits: Vec<_> =
+            hex.chars().filter(|c| !c.is_whitespace()).collect();
+        if digits.is_empty() {
+            return None;
+        }
+        let mut limbs = Vec::with_capacity(digits.len().div_ceil(16));
+        for chunk in digits.rchunks(16) {
+            let s: String = chunk.iter().collect();
+            limbs.push(u64::from_str_radix(&s, 16).ok()?);
+        }
+        Some(Self::from_limbs(limbs))
+    }
+
+    /// Uniformly random in [0, n), from `code::rng`
+    pub fn random_below(n: &BigUint) -> Self {
+        assert!(!n.is_zero());
+        let bits = n.bits();
+        let top_mask = 0xffu8 >> ((8 - bits % 8) % 8);
+        loop {
+            let mut bytes = rng::bytes(bits.div_ceil(8));
+            bytes[0] &= top_mask;
+            let x = Self::from_bytes_be(&bytes);
+            if x < *n {
+                return x;
+            }
+        }
+    }
+
+    /// Uniformly random in [lo, hi)
+    pub fn random_range(lo: &BigUint, hi: &BigUint) -> Self {
+        lo + &Self::random_below(&(hi - lo))
+    }
+
+    fn shl_bits(limbs: &[u64], s: u32) -> Vec<u64> {
+        if s == 0 {
+            return limbs.to_vec();
+        }
+        let mut out = Vec::with_capacity(limbs.len() + 1);
+        let mut carry = 0;
+        for l in limbs {
+            out.push((l << s) | carry);
+            carry = l >> (64 - s);
+        }
+        out.push(carry);
+        out
+    }
+
+    fn shr_bits(limbs: &[u64], s: u32) -> Vec<u64> {
+        if s == 0 {
+            return limbs.to_vec();
+        }
+        let mut out = vec![0; limbs.len()];
+        for i in 0..limbs.len() {
+            let hi = limbs.get(i + 1).map_or(0, |h| h << (64 - s));
+            out[i] = (limbs[i] >> s) | hi;
+        }
+        out
+    }
+
+    /// (quotient, remainder) when dividing by a single limb
+    fn divrem_limb(&self, d: u64) -> (Self, u64) {
+        let mut q = vec![0; self.limbs.len()];
+        let mut r = 0u128;
+        for i in (0..self.limbs.len()).rev() {
+            let num = (r << 64) | self.limbs[i] as u128;
+            q[i] = (num / d as u128) as u64;
+            r = num % d as u128;
+        }
+        (Self::from_limbs(q), r as u64)
+    }
+
+    /// (quotient, remainder). Panics on division by zero.
+    pub fn divrem(&self, d: &BigUint) -> (Self, Self) {
+        assert!(!d.is_zero(), "Division by zero");
+        if self < d {
+            return (Self::zero(), self.clone());
+        }
+        if d.limbs.len() == 1 {
+            let (q, r) = self.divrem_limb(d.limbs[0]);
+            return (q, Self::from(r));
+        }
+        // Knuth vol 2, 4.3.1 algorithm D. Normalized so the top bit of the
+        // divisor is set, which makes the quotient digit guesses close.
+        let s = d.limbs.last().unwrap().leading_zeros();
+        let v = Self::shl_bits(&d.limbs, s);
+        let v = &v[..d.limbs.len()];
+        let mut u = Self::shl_bits(&self.limbs, s);
+        if u.len() == self.limbs.len() {
+            u.push(0);
+        }
+        let n = v.len();
+        let m = u.len() - n;
+        let b = 1u128 << 64;
+        let mut q = vec![0; m];
+        for j in (0..m).rev() {
+            let num = ((u[j + n] as u128) << 64) | u[j + n - 1] as u128;
+            let mut qhat = num / v[n - 1] as u128;
+            let mut rhat = num % v[n - 1] as u128;
+            while qhat >= b
+                || qhat * v[n - 2] as u128
+                    > ((rhat << 64) | u[j + n - 2] as u128)
+            {
+                qhat -= 1;
+                rhat += v[n - 1] as u128;
+                if rhat >= b {
+                    break;
+                }
+            }
+            // u[j..=j+n] -= qhat * v
+            let mut borrow = 0;
+            let mut carry = 0;
+            for i in 0..n {
+                let p = qhat * v[i] as u128 + carry;
+                carry = p >> 64;
+                let (t, b1) = u[i + j].overflowing_sub(p as u64);
+                let (t, b2) = t.overflowing_sub(borrow);
+                u[i + j] = t;
+                borrow = (b1 | b2) as u64;
+            }
+            let (t, b1) = u[j + n].overflowing_sub(carry as u64);
+            let (t, b2) = t.overflowing_sub(borrow);
+            u[j + n] = t;
+            // Rarely qhat is still one too big, add back
+            if b1 | b2 {
+                qhat -= 1;
+                let mut carry = 0;
+                for i in 0..n {
+                    let s = u[i + j] as u128 + v[i] as u128 + carry;
+                    u[i + j] = s as u64;
+                    carry = s >> 64;
+                }
+                u[j + n] = u[j + n].wrapping_add(carry as u64);
+            }
+            q[j] = qhat as u64;
+        }
+        let r = Self::shr_bits(&u[..n], s);
+        (Self::from_limbs(q), Self::from_limbs(r))
+    }
+
+    /// self^exp mod m, square and multiply
+    pub fn modpow(&self, exp: &BigUint, m: &BigUint) -> Self {
+        assert!(!m.is_zero());
+        let base = self % m;
+        let mut acc = Self::one() % m;
+        for i in (0..exp.bits()).rev() {
+            acc = &(&acc * &acc) % m;
+            if exp.bit(i) {
+                acc = &(&acc * &base) % m;
+            }
+        }
+        acc
+    }
+
+    /// (self - other) mod m, for when other might be bigger
+    pub fn sub_mod(&self, other: &BigUint, m: &BigUint) -> Self {
+        let (a, b) = (self % m, other % m);
+        if a >= b {
+            &a - &b
+        } else {
+            &(&a + m) - &b
+        }
+    }
+
+    pub fn to_hex(&self) -> String {
+        format!("{:x}", self)
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(x: u64) -> Self {
+        Self::from_limbs(vec![x])
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, other: &BigUint) -> BigUint {
+        let len = self.limbs.len().max(other.limbs.len());
+        let mut out = Vec::with_capacity(len + 1);
+        let mut carry = false;
+        for i in 0..len {
+            let a = self.limbs.get(i).copied().unwrap_or(0);
+            let b = other.limbs.get(i).copied().unwrap_or(0);
+            let (s, c1) = a.overflowing_add(b);
+            let (s, c2) = s.overflowing_add(carry as u64);
+            out.push(s);
+            carry = c1 | c2;
+        }
+        out.push(carry as u64);
+        BigUint::from_limbs(out)
+    }
+}
+
+/// Panics if the result would be negative
+impl Sub for &BigUint {
+    type Output = BigUint;
+
+    fn sub(self, other: &BigUint) -> BigUint {
+        assert!(*self >= *other, "Subtraction underflow");
+        let mut out = Vec::with_capacity(self.limbs.len());
+        let mut borrow = false;
+        for (i, a) in self.limbs.iter().enumerate() {
+            let b = other.limbs.get(i).copied().unwrap_or(0);
+            let (d, b1) = a.overflowing_sub(b);
+            let (d, b2) = d.overflowing_sub(borrow as u64);
+            out.push(d);
+            borrow = b1 | b2;
+        }
+        BigUint::from_limbs(out)
+    }
+}
+
+impl Mul for &BigUint {
+    type Output = BigUint;
+
+    fn mul(self, other: &BigUint) -> BigUint {
+        let mut out = vec![0u64; self.limbs.len() + other.limbs.len()];
+        for (i, a) in self.limbs.iter().enumerate() {
+            let mut carry = 0u128;
+            for (j, b) in other.limbs.iter().enumerate() {
+                let t = *a as u128 * *b as u128 + out[i + j] as u128 + carry;
+                out[i + j] = t as u64;
+                carry = t >> 64;
+            }
+            out[i + other.limbs.len()] = carry as u64;
+        }
+        BigUint::from_limbs(out)
+    }
+}
+
+impl Div for &BigUint {
+    type Output = BigUint;
+
+    fn div(self, other: &BigUint) -> BigUint {
+        self.divrem(other).0
+    }
+}
+
+impl Rem for &BigUint {
+    type Output = BigUint;
+
+    fn rem(self, other: &BigUint) -> BigUint {
+        self.divrem(other).1
+    }
+}
+
+/// The same operators on owned values
+macro_rules! forward_owned {
+    ($($tr:ident $f:ident),*) => {$(
+        impl $tr for BigUint {
+            type Output = BigUint;
+
+            fn $f(self, other: BigUint) -> BigUint {
+                (&self).$f(&other)
+            }
+        }
+
+        impl $tr<&BigUint> for BigUint {
+            type Output = BigUint;
+
+            fn $f(self, other: &BigUint) -> BigUint {
+                (&self).$f(other)
+            }
+        }
+    )*};
+}
+
+forward_owned!(Add add, Sub sub, Mul mul, Div div, Rem rem);
+
+impl fmt::LowerHex for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.limbs.split_last() {
+            None => write!(f, "0"),
+            Some((top, rest)) => {
+                write!(f, "{:x}", top)?;
+                rest.iter().rev().try_for_each(|l| write!(f, "{:016x}", l))
+            }
+        }
+    }
+}
+
+/// Decimal
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Largest power of 10 in a limb
+        const CHUNK: u64 = 10_000_000_000_000_000_000;
+        let mut chunks = Vec::new();
+        let mut x = self.clone();
+        while !x.is_zero() {
+            let (q, r) = x.divrem_limb(CHUNK);
+            chunks.push(r);
+            x = q;
+        }
+        match chunks.split_last() {
+            None => write!(f, "0"),
+            Some((top, rest)) => {
+                write!(f, "{}", top)?;
+                rest.iter().rev().try_for_each(|c| write!(f, "{:019}", c))
+            }
+        }
+    }
+}
+
+impl fmt::Debug for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "0x{:x}", self)
+    }
+}
+
+#[test]
+fn test_arithmetic() {
+    let hex = |h| BigUint::from_hex(h).unwrap();
+    let a = hex(
+        "123456789abcdef0fedcba9876543210 0f1e2d3c4b5a6978 8796a5b4c3d2e1f0",
+    );
+    let b = hex("fedcba9876543210 0123456789abcdef");
+    assert_eq!(
+        (&a * &b).to_hex(),
+        "121fa00ad77d7423213d0003e234949ab979813701466cec\
+         43f13caed02942a3b5c8ed2268c028a2196fb4e90c1e1f10"
+    );
+    let (q, r) = a.divrem(&b);
+    assert_eq!(q.to_hex(), "1249249249249238ec5397829cbc14e7");
+    assert_eq!(r.to_hex(), "50904d82e20cf9225735967f3e316347");
+    assert_eq!(&(&a + &b) - &b, a);
+
+    let m = hex("ffffffffffffffffffffffffffffff61");
+    let e = hex("10001");
+    let pow = b.modpow(&e, &m);
+    assert_eq!(pow.to_hex(), "803082e5e5e7f2be1d4ab771609992a5");
+    let (three, five) = (BigUint::from(3), BigUint::from(5));
+    assert_eq!(three.sub_mod(&five, &BigUint::from(7)), five);
+
+    assert_eq!(
+        a.to_string(),
+        "82341041235424849065720100320648088507\
+         14215494909037338626692557617960772080"
+    );
+    assert_eq!(BigUint::zero().to_string(), "0");
+    assert_eq!(BigUint::from_bytes_be(&a.to_bytes_be()), a);
+    assert_eq!(BigUint::zero().to_bytes_be(), vec![0]);
+    assert_eq!(BigUint::from(258).to_bytes_be_padded(4), vec![0, 0, 1, 2]);
+    assert_eq!(BigUint::from_bytes_be(&[0, 0, 1]), BigUint::one());
+
+    // Division against multiplication, on sizes that exercise the add back
+    for _ in 0..200 {
+        let x = BigUint::from_bytes_be(&rng::bytes(64));
+        let y = BigUint::from_bytes_be(&rng::bytes(rng::interval(1, 40)));
+        if y.is_zero() {
+            continue;
+        }
+        let (q, r) = x.divrem(&y);
+        assert!(r < y);
+        assert_eq!(&(&q * &y) + &r, x);
+        assert!(BigUint::random_below(&y) < y);
+    }
+}
@@ -0,0 +1,206 @@
+//! Finite field Diffie-Hellman, challenge 33 onwards.
+//!
+//! Both sides pick a private `a`, send `g^a mod p` and end up with the
+//! shared `g^ab mod p`. Key material comes from `code::rng`, so a fixed
+//! `CRYPTOPALS_SEED` replays a run.
+
+use crate::code;
+use code::aes128::{self, Aes128};
+use code::bigint::BigUint;
+use code::hash::Sha1;
+
+/// RFC 3526, 1536 bits
+const MODP_1536: &str = "
+    FFFFFFFF FFFFFFFF C90FDAA2 2168C234 C4C6628B 80DC1CD1 29024E08 8A67CC74
+    020BBEA6 3B139B22 514A0879 8E3404DD EF9519B3 CD3A431B 302B0A6D F25F1437
+    4FE1356D 6D51C245 E485B576 625E7EC6 F44C42E9 A637ED6B 0BFF5CB6 F406B7ED
+    EE386BFB 5A899FA5 AE9F2411 7C4B1FE6 49286651 ECE45B3D C2007CB8 A163BF05
+    98DA4836 1C55D39A 69163FA8 FD24CF5F 83655D23 DCA3AD96 1C62F356 208552BB
+    9ED52907 7096966D 670C354E 4ABC9804 F1746C08 CA237327 FFFFFFFF FFFFFFFF";
+
+/// RFC 3526, 2048 bits
+const MODP_2048: &str = "
+    FFFFFFFF FFFFFFFF C90FDAA2 2168C234 C4C6628B 80DC1CD1 29024E08 8A67CC74
+    020BBEA6 3B139B22 514A0879 8E3404DD EF9519B3 CD3A431B 302B0A6D F25F1437
+    4FE1356D 6D51C245 E485B576 625E7EC6 F44C42E9 A637ED6B 0BFF5CB6 F406B7ED
+    EE386BFB 5A899FA5 AE9F2411 7C4B1FE6 49286651 ECE45B3D C2007CB8 A163BF05
+    98DA4836 1C55D39A 69163FA8 FD24CF5F 83655D23 DCA3AD96 1C62F356 208552BB
+    9ED52907 7096966D 670C354E 4ABC9804 F1746C08 CA18217C 32905E46 2E36CE3B
+    E39E772C 180E8603 9B2783A2 EC07A28F B5C55DF0 6F4C52C9 DE2BCBF6 95581718
+    3995497C EA956AE5 15D22618 98FA0510 15728E5A 8AACAA68 FFFFFFFF FFFFFFFF";
+
+/// RFC 3526, 3072 bits
+const MODP_3072: &str = "
+    FFFFFFFF FFFFFFFF C90FDAA2 2168C234 C4C6628B 80DC1CD1 29024E08 8A67CC74
+    020BBEA6 3B139B22 514A0879 8E3404DD EF9519B3 CD3A431B 302B0A6D F25F1437
+    4FE1356D 6D51C245 E485B576 625E7EC6 F44C42E9 A637ED6B 0BFF5CB6 F406B7ED
+    EE386BFB 5A899FA5 AE9F2411 7C4B1FE6 49286651 ECE45B3D C2007CB8 A163BF05
+    98DA4836 1C55D39A 69163FA8 FD24CF5F 83655D23 DCA3AD96 1C62F356 208552BB
+    9ED52907 7096966D 670C354E 4ABC9804 F1746C08 CA18217C 32905E46 2E36CE3B
+    E39E772C 180E8603 9B2783A2 EC07A28F B5C55DF0 6F4C52C9 DE2BCBF6 95581718
+    3995497C EA956AE5 15D22618 98FA0510 15728E5A 8AAAC42D AD33170D 04507A33
+    A85521AB DF1CBA64 ECFB8504 58DBEF0A 8AEA7157 5D060C7D B3970F85 A6E1E4C7
+    ABF5AE8C DB0933D7 1E8C94E0 4A25619D CEE3D226 1AD2EE6B F12FFA06 D98A0864
+    D8760273 3EC86A64 521F2B18 177B200C BBE11757 7A615D6C 770988C0 BAD946E2
+    08E24FA0 74E5AB31 43DB5BFC E0FD108E 4B82D120 A93AD2CA FFFFFFFF FFFFFFFF";
+
+/// RFC 3526, 4096 bits
+const MODP_4096: &str = "
+    FFFFFFFF FFFFFFFF C90FDAA2 2168C234 C4C6628B 80DC1CD1 29024E08 8A67CC74
+    020BBEA6 3B139B22 514A0879 8E3404DD EF9519B3 CD3A431B 302B0A6D F25F1437
+    4FE1356D 6D51C245 E485B576 625E7EC6 F44C42E9 A637ED6B 0BFF5CB6 F406B7ED
+    EE386BFB 5A899FA5 AE9F2411 7C4B1FE6 49286651 ECE45B3D C2007CB8 A163BF05
+    98DA4836 1C55D39A 69163FA8 FD24CF5F 83655D23 DCA3AD96 1C62F356 208552BB
+    9ED52907 7096966D 670C354E 4ABC9804 F1746C08 CA18217C 32905E46 2E36CE3B
+    E39E772C 180E8603 9B2783A2 EC07A28F B5C55DF0 6F4C52C9 DE2BCBF6 95581718
+    3995497C EA956AE5 15D22618 98FA0510 15728E5A 8AAAC42D AD33170D 04507A33
+    A85521AB DF1CBA64 ECFB8504 58DBEF0A 8AEA7157 5D060C7D B3970F85 A6E1E4C7
+    ABF5AE8C DB0933D7 1E8C94E0 4A25619D CEE3D226 1AD2EE6B F12FFA06 D98A0864
+    D8760273 3EC86A64 521F2B18 177B200C BBE11757 7A615D6C 770988C0 BAD946E2
+    08E24FA0 74E5AB31 43DB5BFC E0FD108E 4B82D120 A9210801 1A723C12 A787E6D7
+    88719A10 BDBA5B26 99C32718 6AF4E23C 1A946834 B6150BDA 2583E9CA 2AD44CE8
+    DBBBC2DB 04DE8EF9 2E8EFC14 1FBECAA6 287C5947 4E6BC05D 99B2964F A090C3A2
+    233BA186 515BE7ED 1F612970 CEE2D7AF B81BDD76 2170481C D0069127 D5B05AA9
+    93B4EA98 8D8FDDC1 86FFB7DC 90A6C08F 4DF435C9 34063199 FFFFFFFF FFFFFFFF";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Group {
+    pub p: BigUint,
+    pub g: BigUint,
+}
+
+impl Group {
+    pub fn new(p: BigUint, g: BigUint) -> Self {
+        assert!(p > BigUint::from(3), "p too small");
+        Self { p, g }
+    }
+
+    /// p = 37, g = 5, the warm up from challenge 33
+    pub fn toy() -> Self {
+        Self::new(BigUint::from(37), BigUint::from(5))
+    }
+
+    /// The RFC 3526 group of `bits` bits with g = 2. 1536 is the one from
+    /// challenge 33.
+    pub fn modp(bits: usize) -> Option<Self> {
+        let hex = match bits {
+            1536 => MODP_1536,
+            2048 => MODP_2048,
+            3072 => MODP_3072,
+            4096 => MODP_4096,
+            _ => return None,
+        };
+        let p = BigUint::from_hex(hex).unwrap();
+        Some(Self::new(p, BigUint::from(2)))
+    }
+
+    /// Bytes needed for an element
+    pub fn element_len(&self) -> usize {
+        self.p.bits().div_ceil(8)
+    }
+
+    /// Random in [2, p - 2], so neither `x` nor `g^x` is trivial
+    pub fn random_private(&self) -> BigUint {
+        let two = BigUint::from(2);
+        BigUint::random_range(&two, &(&self.p - &BigUint::one()))
+    }
+}
+
+impl Default for Group {
+    fn default() -> Self {
+        Self::modp(1536).unwrap()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct KeyPair {
+    private: BigUint,
+    public: BigUint,
+}
+
+impl KeyPair {
+    pub fn new(group: &Group) -> Self {
+        Self::from_private(group, group.random_private())
+    }
+
+    pub fn from_private(group: &Group, private: BigUint) -> Self {
+        let public = group.g.modpow(&private, &group.p);
+        Self { private, public }
+    }
+
+    pub fn public(&self) -> &BigUint {
+        &self.public
+    }
+
+    pub fn private(&self) -> &BigUint {
+        &self.private
+    }
+
+    /// other^private mod p
+    pub fn shared(&self, group: &Group, other: &BigUint) -> BigUint {
+        other.modpow(&self.private, &group.p)
+    }
+}
+
+/// The first 16 bytes of SHA1 of the big endian shared secret
+pub fn session_key(secret: &BigUint) -> [u8; aes128::BLOCKSIZE] {
+    let digest = Sha1::digest(&secret.to_bytes_be());
+    let mut key = [0; aes128::BLOCKSIZE];
+    key.copy_from_slice(&digest[..aes128::BLOCKSIZE]);
+    key
+}
+
+/// AES-CBC with a random IV, sent as `cipher || iv` like challenge 34
+pub fn encrypt(key: &[u8], plain: &[u8]) -> Vec<u8> {
+    let iv = code::rnd(aes128::BLOCKSIZE);
+    let mut msg = Vec::from(plain);
+    if msg.is_empty() {
+        // cbc_encr leaves empty input alone, so pad here instead
+        code::pkcs7_pad(&mut msg, aes128::BLOCKSIZE as u8);
+        Aes128::new(key).cbc_encr(&iv, &mut msg);
+        msg.truncate(aes128::BLOCKSIZE);
+    } else {
+        Aes128::new(key).cbc_encr(&iv, &mut msg);
+    }
+    msg.extend_from_slice(&iv);
+    msg
+}
+
+/// Undoes `encrypt`, None if the length or padding is off
+pub fn decrypt(key: &[u8], msg: &[u8]) -> Option<Vec<u8>> {
+    let bs = aes128::BLOCKSIZE;
+    if msg.len() < 2 * bs || !msg.len().is_multiple_of(bs) {
+        return None;
+    }
+    let (cipher, iv) = msg.split_at(msg.len() - bs);
+    let mut plain = Vec::from(cipher);
+    Aes128::new(key).cbc_decr(iv, &mut plain);
+    let len = code::pkcs7_validate(&plain)?.len();
+    plain.truncate(len);
+    Some(plain)
+}
+
+#[test]
+fn test_shared_secret() {
+    for group in [Group::toy(), Group::default(), Group::modp(2048).unwrap()] {
+        let (a, b) = (KeyPair::new(&group), KeyPair::new(&group));
+        let s = a.shared(&group, b.public());
+        assert_eq!(s, b.shared(&group, a.public()));
+        assert!(s < group.p);
+    }
+    // 5^7 = 78125 = 2111 * 37 + 18
+    let toy = Group::toy();
+    assert_eq!(
+        KeyPair::from_private(&toy, BigUint::from(7)).public,
+        18.into()
+    );
+    assert_eq!(Group::modp(4096).unwrap().element_len(), 512);
+    assert_eq!(Group::modp(1024), None);
+
+    let key = session_key(&BigUint::from(1));
+    for plain in &[&b""[..], b"YELLOW SUBMARINE", b"hello"] {
+        let msg = encrypt(&key, plain);
+        assert_eq!(msg.len() % aes128::BLOCKSIZE, 0);
+        assert_eq!(decrypt(&key, &msg).as_deref(), Some(*plain));
+    }
+}
@@ -0,0 +1,207 @@
+//! Buffers shown block by block, to see what lines up where.
+//!
+//! Every line is one block: its index, byte offset, the bytes in hex and
+//! then as ascii. Blocks that occur more than once get the same colour (and
+//! a `#n` tag), which is what ECB looks like.
+
+use crate::code::encoding::hexdump::printable;
+
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Output {
+    /// No colours, only the tags
+    Plain,
+    /// Terminal escape codes
+    Ansi,
+    /// A `<pre>` with inline styles
+    Html,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mark {
+    None,
+    /// Repeated block number n
+    Repeat(usize),
+    /// Differs from the other buffer
+    Changed,
+}
+
+const ANSI_COLOURS: &[&str] =
+    &["32", "33", "34", "35", "36", "92", "93", "94"];
+const HTML_COLOURS: &[&str] = &[
+    "#c8f7c5", "#fdf3b3", "#c5daf7", "#f0c5f7", "#c5f3f7", "#e0e0e0",
+];
+
+pub struct View {
+    blocksize: usize,
+    output: Output,
+}
+
+impl View {
+    pub fn new(blocksize: usize) -> Self {
+        assert!(blocksize > 0);
+        Self {
+            blocksize,
+            output: Output::Plain,
+        }
+    }
+
+    pub fn with_output(mut self, output: Output) -> Self {
+        self.output = output;
+        self
+    }
+
+    fn paint(&self, text: &str, mark: Mark) -> String {
+        let text = match self.output {
+            Output::Html => text
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;"),
+            _ => String::from(text),
+        };
+        match (self.output, mark) {
+            (Output::Plain, _) | (_, Mark::None) => text,
+            (Output::Ansi, Mark::Repeat(n)) => {
+                let c = ANSI_COLOURS[n % ANSI_COLOURS.len()];
+                format!("\x1b[{}m{}\x1b[0m", c, text)
+            }
+            (Output::Ansi, Mark::Changed) => {
+                format!("\x1b[1;31m{}\x1b[0m", text)
+            }
+            (Output::Html, Mark::Repeat(n)) => {
+                let c = HTML_COLOURS[n % HTML_COLOURS.len()];
+                format!("<span style=\"background:{}\">{}</span>", c, text)
+            }
+            (Output::Html, Mark::Changed) => {
+                format!("<span style=\"color:#d00\"><b>{}</b></span>", text)
+            }
+        }
+    }
+
+    fn wrap(&self, body: String) -> String {
+        match self.output {
+            Output::Html => format!("<pre>\n{}</pre>\n", body),
+            _ => body,
+        }
+    }
+
+    /// Hex and ascii columns of one block, every byte marked on its own.
+    /// Missing bytes (`None`) are blank, so short blocks still line up.
+    fn columns(&self, bytes: &[Option<u8>], marks: &[Mark]) -> String {
+        let hex: Vec<_> = bytes
+            .iter()
+            .zip(marks)
+            .map(|(b, m)| match b {
+                Some(b) => self.paint(&format!("{:02x}", b), *m),
+                None => String::from("  "),
+            })
+            .collect();
+        let ascii: String = bytes
+            .iter()
+            .zip(marks)
+            .map(|(b, m)| match b {
+                Some(b) => self.paint(&printable(*b).to_string(), *m),
+                None => String::from(" "),
+            })
+            .collect();
+        format!("{}  |{}|", hex.join(" "), ascii)
+    }
+
+    fn padded(&self, block: &[u8]) -> Vec<Option<u8>> {
+        (0..self.blocksize).map(|i| block.get(i).copied()).collect()
+    }
+
+    /// Every block on its own line, repeated blocks highlighted
+    pub fn blocks(&self, buf: &[u8]) -> String {
+        let mut counts: HashMap<&[u8], usize> = HashMap::new();
+        for block in buf.chunks(self.blocksize) {
+            *counts.entry(block).or_insert(0) += 1;
+        }
+        // Numbered in order of first appearance
+        let mut repeats: HashMap<&[u8], usize> = HashMap::new();
+        let mut out = String::new();
+        for (i, block) in buf.chunks(self.blocksize).enumerate() {
+            let mark = if counts[block] > 1 {
+                let next = repeats.len();
+                Mark::Repeat(*repeats.entry(block).or_insert(next))
+            } else {
+                Mark::None
+            };
+            let marks = vec![mark; self.blocksize];
+            let tag = match mark {
+                Mark::Repeat(n) => format!(" #{}", n),
+                _ => String::new(),
+            };
+            out += &format!(
+                "{:4} {:06x}  {}{}\n",
+                i,
+                i * self.blocksize,
+                self.columns(&self.padded(block), &marks),
+                tag
+            );
+        }
+        self.wrap(out)
+    }
+
+    /// `a` and `b` block by block, one above the other, differing bytes
+    /// highlighted. Identical blocks are tagged `=`.
+    /// Without colours, a line of `^^` marks the differing bytes.
+    pub fn diff(&self, a: &[u8], b: &[u8]) -> String {
+        let nblocks = a.len().max(b.len()).div_ceil(self.blocksize);
+        let block_of = |buf: &[u8], i: usize| -> Vec<Option<u8>> {
+            (0..self.blocksize)
+                .map(|j| buf.get(i * self.blocksize + j).copied())
+                .collect()
+        };
+        let mut out = String::new();
+        for i in 0..nblocks {
+            let (x, y) = (block_of(a, i), block_of(b, i));
+            let marks: Vec<_> = x
+                .iter()
+                .zip(&y)
+                .map(|(p, q)| if p == q { Mark::None } else { Mark::Changed })
+                .collect();
+            let same = marks.iter().all(|m| *m == Mark::None);
+            let tag = if same { " =" } else { "" };
+            out += &format!(
+                "{:4} {:06x} a {}{}\n",
+                i,
+                i * self.blocksize,
+                self.columns(&x, &marks),
+                tag
+            );
+            out += &format!("            b {}\n", self.columns(&y, &marks));
+            if self.output == Output::Plain && !same {
+                let carets: Vec<_> = marks
+                    .iter()
+                    .map(|m| if *m == Mark::Changed { "^^" } else { "  " })
+                    .collect();
+                let carets = carets.join(" ");
+                out += &format!("              {}\n", carets.trim_end());
+            }
+        }
+        self.wrap(out)
+    }
+}
+
+#[test]
+fn test_blocks_and_diff() {
+    let buf = b"YELLOW SUBMARINE0123456789abcdefYELLOW SUBMARINEx";
+    let shown = View::new(16).blocks(buf);
+    let lines: Vec<_> = shown.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].ends_with("|YELLOW SUBMARINE| #0"));
+    assert!(lines[1].ends_with('|'));
+    assert!(lines[2].ends_with(" #0"));
+    assert!(lines[3].contains("|x               |"));
+
+    let html = View::new(4).with_output(Output::Html).blocks(b"<&>");
+    assert!(html.contains("|&lt;&amp;&gt; |"));
+
+    let diff = View::new(4).diff(b"abcdefgh", b"abcdeXgh!");
+    let lines: Vec<_> = diff.lines().collect();
+    assert!(lines[0].ends_with('='));
+    assert!(lines[4].trim_start().starts_with("^^"));
+    assert_eq!(lines.len(), 8);
+}
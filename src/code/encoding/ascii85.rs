@@ -0,0 +1,94 @@
+//! Adobe's Ascii85. `z` is short for four zero bytes.
+//! Encoding leaves out the `<~ ~>` delimiters, decoding accepts them.
+
+use super::DecodeError;
+
+const ZERO_GROUP: u8 = b'z';
+
+pub fn encode(inp: &[u8]) -> String {
+    let mut out = String::with_capacity(inp.len().div_ceil(4) * 5);
+    for chunk in inp.chunks(4) {
+        let mut group = [0u8; 4];
+        group[..chunk.len()].copy_from_slice(chunk);
+        let mut v = u32::from_be_bytes(group);
+        if v == 0 && chunk.len() == 4 {
+            out.push(ZERO_GROUP as char);
+            continue;
+        }
+        let mut digits = [0u8; 5];
+        for d in digits.iter_mut().rev() {
+            *d = b'!' + (v % 85) as u8;
+            v /= 85;
+        }
+        // A partial group of n bytes needs n+1 chars
+        digits[..(chunk.len() + 1)]
+            .iter()
+            .for_each(|d| out.push(*d as char));
+    }
+    out
+}
+
+/// Whitespace is ignored
+pub fn decode(inp: &str) -> Result<Vec<u8>, DecodeError> {
+    let trimmed = inp.trim();
+    let inp = match trimmed.strip_prefix("<~") {
+        Some(rest) => rest
+            .strip_suffix("~>")
+            .ok_or(DecodeError::InvalidPadding)?,
+        None => trimmed,
+    };
+    let mut out = Vec::with_capacity(inp.len() / 5 * 4);
+    // (offset, digit) of the current group
+    let mut group: Vec<(usize, u8)> = Vec::with_capacity(5);
+    let mut flush = |group: &mut Vec<(usize, u8)>| {
+        let n = group.len();
+        let offset = group[0].0;
+        // Pad a partial group with the highest digit
+        let v = group
+            .iter()
+            .map(|(_, d)| *d as u64)
+            .chain(std::iter::repeat(84))
+            .take(5)
+            .fold(0u64, |acc, d| acc * 85 + d);
+        if v > u32::MAX as u64 {
+            return Err(DecodeError::Overflow { offset });
+        }
+        out.extend(&(v as u32).to_be_bytes()[..(n - 1)]);
+        group.clear();
+        Ok(())
+    };
+    for (offset, c) in inp.bytes().enumerate() {
+        match c {
+            c if (c as char).is_ascii_whitespace() => continue,
+            ZERO_GROUP if group.is_empty() => {
+                group.extend(&[(offset, 0); 5]);
+            }
+            b'!'..=b'u' => group.push((offset, c - b'!')),
+            _ => return Err(DecodeError::InvalidByte { offset, byte: c }),
+        }
+        if group.len() == 5 {
+            flush(&mut group)?;
+        }
+    }
+    match group.len() {
+        0 => {}
+        1 => return Err(DecodeError::InvalidLength(inp.len())),
+        _ => flush(&mut group)?,
+    }
+    Ok(out)
+}
+
+#[test]
+fn test_vectors() {
+    let plain = b"Man is distinguished";
+    let a85 = "9jqo^BlbD-BleB1DJ+*+F(f,q";
+    assert_eq!(encode(plain), a85);
+    assert_eq!(decode(a85).unwrap(), &plain[..]);
+    assert_eq!(decode("<~9jqo^BlbD-Ble\nB1DJ+*+F(f,q~>").unwrap(), &plain[..]);
+    assert_eq!(encode(&[0, 0, 0, 0, 0]), "z!!");
+    assert_eq!(decode("z!!").unwrap(), vec![0; 5]);
+    assert_eq!(encode(b"."), "/c");
+    assert_eq!(decode("/c").unwrap(), b".");
+    assert_eq!(decode("s8W-\""), Err(DecodeError::Overflow { offset: 0 }));
+    assert!(decode("/").is_err());
+}
@@ -0,0 +1,52 @@
+//! RFC 4648 base32, always padded
+
+use super::DecodeError;
+
+const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+pub const PADDING: u8 = b'=';
+
+pub fn encode(inp: &[u8]) -> String {
+    let mut out = super::pack(inp, ALPHABET, 5);
+    while !out.len().is_multiple_of(8) {
+        out.push(PADDING as char);
+    }
+    out
+}
+
+pub fn decode(inp: &str) -> Result<Vec<u8>, DecodeError> {
+    let inp = inp.as_bytes();
+    if !inp.len().is_multiple_of(8) {
+        return Err(DecodeError::InvalidLength(inp.len()));
+    }
+    let padlen = inp.iter().rev().take_while(|c| **c == PADDING).count();
+    // Only 1, 3, 4 or 6 padding chars can come out of `encode`
+    if ![0, 1, 3, 4, 6].contains(&padlen) {
+        return Err(DecodeError::InvalidPadding);
+    }
+    let data = &inp[..(inp.len() - padlen)];
+    if data.contains(&PADDING) {
+        return Err(DecodeError::InvalidPadding);
+    }
+    super::unpack(data, ALPHABET, 5)
+}
+
+#[test]
+fn test_rfc4648() {
+    let vectors = [
+        ("", ""),
+        ("f", "MY======"),
+        ("fo", "MZXQ===="),
+        ("foo", "MZXW6==="),
+        ("foob", "MZXW6YQ="),
+        ("fooba", "MZXW6YTB"),
+        ("foobar", "MZXW6YTBOI======"),
+    ];
+    for (plain, b32) in vectors.iter() {
+        assert_eq!(encode(plain.as_bytes()), *b32);
+        assert_eq!(decode(b32).unwrap(), plain.as_bytes());
+    }
+    assert!(decode("MZXW6===A").is_err());
+    assert!(decode("M=======").is_err());
+    assert!(decode("mzxw6===").is_err());
+}
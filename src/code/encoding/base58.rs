@@ -0,0 +1,74 @@
+//! Base58 with Bitcoin's alphabet, no checksum.
+//! Every leading zero byte becomes a leading `1`.
+//! OBS: quadratic in the length, meant for keys and hashes.
+
+use super::DecodeError;
+
+const ALPHABET: &[u8; 58] =
+    b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// `digits` is a big-endian number in base `from`, returns it in base `to`
+fn convert(digits: &[u8], from: u32, to: u32) -> Vec<u8> {
+    // Little-endian digits in base `to`
+    let mut out: Vec<u8> = Vec::new();
+    for d in digits {
+        let mut carry = *d as u32;
+        for x in out.iter_mut() {
+            carry += *x as u32 * from;
+            *x = (carry % to) as u8;
+            carry /= to;
+        }
+        while carry > 0 {
+            out.push((carry % to) as u8);
+            carry /= to;
+        }
+    }
+    out.reverse();
+    out
+}
+
+pub fn encode(inp: &[u8]) -> String {
+    let zeros = inp.iter().take_while(|b| **b == 0).count();
+    let digits = convert(&inp[zeros..], 256, 58);
+    std::iter::repeat_n(b'1', zeros)
+        .chain(digits.iter().map(|d| ALPHABET[*d as usize]))
+        .map(|c| c as char)
+        .collect()
+}
+
+pub fn decode(inp: &str) -> Result<Vec<u8>, DecodeError> {
+    let table = super::lookup(ALPHABET);
+    let digits = inp
+        .bytes()
+        .enumerate()
+        .map(|(offset, c)| match table[c as usize] {
+            0xff => Err(DecodeError::InvalidByte { offset, byte: c }),
+            v => Ok(v),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let zeros = digits.iter().take_while(|d| **d == 0).count();
+    let mut out = vec![0; zeros];
+    out.extend(convert(&digits[zeros..], 58, 256));
+    Ok(out)
+}
+
+#[test]
+fn test_vectors() {
+    let vectors: [(&[u8], &str); 4] = [
+        (b"", ""),
+        (b"Hello World!", "2NEpo7TZRRrLZSi2U"),
+        (&[0, 0, 0x28, 0x7f, 0xb4, 0xcd], "11233QC4"),
+        (&[0], "1"),
+    ];
+    for (plain, b58) in vectors.iter() {
+        assert_eq!(encode(plain), *b58);
+        assert_eq!(decode(b58).unwrap(), *plain);
+    }
+    assert_eq!(
+        decode("10"),
+        Err(DecodeError::InvalidByte {
+            offset: 1,
+            byte: b'0'
+        })
+    );
+}
@@ -0,0 +1,403 @@
+//! RFC 4648 base64, standard and URL-safe alphabets
+
+use super::DecodeError;
+
+use std::io::{self, Read, Write};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    pub alphabet: &'static [u8; 64],
+    /// Pad with `=` to a multiple of 4 chars
+    pub pad: bool,
+}
+
+const STANDARD_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const URL_SAFE_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+pub const STANDARD: Config = Config {
+    alphabet: STANDARD_ALPHABET,
+    pad: true,
+};
+pub const STANDARD_NO_PAD: Config = Config {
+    alphabet: STANDARD_ALPHABET,
+    pad: false,
+};
+pub const URL_SAFE: Config = Config {
+    alphabet: URL_SAFE_ALPHABET,
+    pad: true,
+};
+pub const URL_SAFE_NO_PAD: Config = Config {
+    alphabet: URL_SAFE_ALPHABET,
+    pad: false,
+};
+
+pub const PADDING: u8 = b'=';
+
+pub fn encode(inp: &[u8], cfg: Config) -> String {
+    let mut out = super::pack(inp, cfg.alphabet, 6);
+    if cfg.pad {
+        while !out.len().is_multiple_of(4) {
+            out.push(PADDING as char);
+        }
+    }
+    out
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Only exactly what `encode` outputs with the same `Config`
+    Strict,
+    /// Ignores whitespace, like `code::decode_base64`, and padding is
+    /// optional
+    Lenient,
+}
+
+/// Decodes one symbol at a time, so input can come in any pieces
+struct DecodeState {
+    cfg: Config,
+    mode: Mode,
+    table: [u8; 256],
+    /// Values of the symbols in the current group of 4
+    quad: [u8; 4],
+    n: usize,
+    /// Padding chars after the `n` symbols
+    pad: usize,
+    /// A padded group has ended the data
+    done: bool,
+    /// All non-whitespace so far
+    symbols: usize,
+    /// Offset and byte of the last symbol, where bad trailing bits are
+    last: (usize, u8),
+    offset: usize,
+}
+
+impl DecodeState {
+    fn new(cfg: Config, mode: Mode) -> Self {
+        Self {
+            cfg,
+            mode,
+            table: super::lookup(cfg.alphabet),
+            quad: [0; 4],
+            n: 0,
+            pad: 0,
+            done: false,
+            symbols: 0,
+            last: (0, 0),
+            offset: 0,
+        }
+    }
+
+    fn feed(
+        &mut self,
+        inp: &[u8],
+        out: &mut Vec<u8>,
+    ) -> Result<(), DecodeError> {
+        for &c in inp {
+            let offset = self.offset;
+            self.offset += 1;
+            if self.mode == Mode::Lenient && c.is_ascii_whitespace() {
+                continue;
+            }
+            self.symbols += 1;
+            if c == PADDING {
+                if self.mode == Mode::Strict && !self.cfg.pad {
+                    return Err(DecodeError::InvalidByte { offset, byte: c });
+                }
+                if self.done || self.n < 2 {
+                    return Err(DecodeError::InvalidPadding);
+                }
+                self.pad += 1;
+                if self.n + self.pad == 4 {
+                    self.flush(out)?;
+                    self.done = true;
+                }
+                continue;
+            }
+            let v = self.table[c as usize];
+            if v == 0xff {
+                return Err(DecodeError::InvalidByte { offset, byte: c });
+            }
+            if self.done || self.pad > 0 {
+                return Err(DecodeError::InvalidPadding);
+            }
+            self.quad[self.n] = v;
+            self.n += 1;
+            self.last = (offset, c);
+            if self.n == 4 {
+                self.flush(out)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Outputs the whole bytes in `quad` and starts a new group
+    fn flush(&mut self, out: &mut Vec<u8>) -> Result<(), DecodeError> {
+        let bits = 6 * self.n;
+        let acc = self.quad[..self.n]
+            .iter()
+            .fold(0u32, |acc, v| (acc << 6) | *v as u32);
+        if acc & ((1 << (bits % 8)) - 1) != 0 {
+            let (offset, byte) = self.last;
+            return Err(DecodeError::InvalidByte { offset, byte });
+        }
+        for i in 1..=(bits / 8) {
+            out.push((acc >> (bits - 8 * i)) as u8);
+        }
+        self.n = 0;
+        self.pad = 0;
+        Ok(())
+    }
+
+    fn finish(&mut self, out: &mut Vec<u8>) -> Result<(), DecodeError> {
+        if self.pad > 0 {
+            return Err(DecodeError::InvalidPadding);
+        }
+        match self.n {
+            0 => Ok(()),
+            1 => Err(DecodeError::InvalidLength(self.symbols)),
+            _ if self.mode == Mode::Strict && self.cfg.pad => {
+                Err(DecodeError::InvalidLength(self.symbols))
+            }
+            _ => self.flush(out),
+        }
+    }
+}
+
+fn decode_mode(
+    inp: &str,
+    cfg: Config,
+    mode: Mode,
+) -> Result<Vec<u8>, DecodeError> {
+    let mut out = Vec::with_capacity(inp.len() / 4 * 3);
+    let mut state = DecodeState::new(cfg, mode);
+    state.feed(inp.as_bytes(), &mut out)?;
+    state.finish(&mut out)?;
+    Ok(out)
+}
+
+/// Padding must be there exactly when `cfg.pad`
+pub fn decode(inp: &str, cfg: Config) -> Result<Vec<u8>, DecodeError> {
+    decode_mode(inp, cfg, Mode::Strict)
+}
+
+pub fn decode_lenient(inp: &str, cfg: Config) -> Result<Vec<u8>, DecodeError> {
+    decode_mode(inp, cfg, Mode::Lenient)
+}
+
+/// Reads base64 from `inner` and hands out the decoded bytes.
+/// Bad input is an `io::ErrorKind::InvalidData` error wrapping a
+/// `DecodeError`.
+pub struct Decoder<R> {
+    inner: R,
+    state: DecodeState,
+    out: Vec<u8>,
+    pos: usize,
+    eof: bool,
+}
+
+impl<R: Read> Decoder<R> {
+    pub fn new(inner: R, cfg: Config, mode: Mode) -> Self {
+        Self {
+            inner,
+            state: DecodeState::new(cfg, mode),
+            out: Vec::new(),
+            pos: 0,
+            eof: false,
+        }
+    }
+}
+
+impl<R: Read> Read for Decoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut chunk = [0u8; 1024];
+        while self.pos == self.out.len() && !self.eof {
+            self.out.clear();
+            self.pos = 0;
+            let n = self.inner.read(&mut chunk)?;
+            let res = if n == 0 {
+                self.eof = true;
+                self.state.finish(&mut self.out)
+            } else {
+                self.state.feed(&chunk[..n], &mut self.out)
+            };
+            res.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        }
+        let n = buf.len().min(self.out.len() - self.pos);
+        buf[..n].copy_from_slice(&self.out[self.pos..(self.pos + n)]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// Writes the base64 of everything written to it to `inner`.
+/// The last partial group is written by `finish`, or when dropped.
+pub struct Encoder<W: Write> {
+    inner: Option<W>,
+    cfg: Config,
+    /// Up to 2 bytes not yet a whole group
+    pending: Vec<u8>,
+}
+
+impl<W: Write> Encoder<W> {
+    pub fn new(inner: W, cfg: Config) -> Self {
+        Self {
+            inner: Some(inner),
+            cfg,
+            pending: Vec::with_capacity(3),
+        }
+    }
+
+    fn write_tail(&mut self) -> io::Result<()> {
+        if let Some(inner) = self.inner.as_mut() {
+            let tail = encode(&self.pending, self.cfg);
+            self.pending.clear();
+            inner.write_all(tail.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Writes the last group, with padding, and returns `inner`
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_tail()?;
+        Ok(self.inner.take().unwrap())
+    }
+}
+
+impl<W: Write> Write for Encoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let alphabet = self.cfg.alphabet;
+        let inner = self.inner.as_mut().unwrap();
+        let mut rest = buf;
+        if !self.pending.is_empty() {
+            let take = (3 - self.pending.len()).min(rest.len());
+            self.pending.extend(&rest[..take]);
+            rest = &rest[take..];
+            if self.pending.len() < 3 {
+                return Ok(buf.len());
+            }
+            let group = super::pack(&self.pending, alphabet, 6);
+            inner.write_all(group.as_bytes())?;
+            self.pending.clear();
+        }
+        let whole = rest.len() / 3 * 3;
+        inner.write_all(super::pack(&rest[..whole], alphabet, 6).as_bytes())?;
+        self.pending.extend(&rest[whole..]);
+        Ok(buf.len())
+    }
+
+    /// OBS: can't write a partial group before `finish`
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.as_mut().unwrap().flush()
+    }
+}
+
+impl<W: Write> Drop for Encoder<W> {
+    fn drop(&mut self) {
+        let _ = self.write_tail();
+    }
+}
+
+#[test]
+fn test_rfc4648() {
+    let vectors = [
+        ("", ""),
+        ("f", "Zg=="),
+        ("fo", "Zm8="),
+        ("foo", "Zm9v"),
+        ("foob", "Zm9vYg=="),
+        ("fooba", "Zm9vYmE="),
+        ("foobar", "Zm9vYmFy"),
+    ];
+    for (plain, b64) in vectors.iter() {
+        assert_eq!(encode(plain.as_bytes(), STANDARD), *b64);
+        assert_eq!(decode(b64, STANDARD).unwrap(), plain.as_bytes());
+        let unpadded = b64.trim_end_matches('=');
+        let decoded = decode(unpadded, STANDARD_NO_PAD).unwrap();
+        assert_eq!(decoded, plain.as_bytes());
+    }
+    assert_eq!(encode(&[0xfb, 0xff], URL_SAFE_NO_PAD), "-_8");
+    assert_eq!(decode("Zm9", STANDARD), Err(DecodeError::InvalidLength(3)));
+    assert_eq!(decode("Zm8", STANDARD_NO_PAD).unwrap(), b"fo");
+    assert!(decode("Zm9=", STANDARD).is_err());
+    assert!(decode("Z===", STANDARD).is_err());
+    assert!(decode("Zm9v", URL_SAFE).is_ok());
+    assert!(decode("+/==", URL_SAFE).is_err());
+}
+
+/// Reads at most `max` bytes per call
+#[cfg(test)]
+struct Trickle<'a>(&'a [u8], usize);
+
+#[cfg(test)]
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.1).min(self.0.len());
+        buf[..n].copy_from_slice(&self.0[..n]);
+        self.0 = &self.0[n..];
+        Ok(n)
+    }
+}
+
+/// Random inputs against the `base64` crate
+#[test]
+fn test_against_crate() {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(37);
+    for _ in 0..500 {
+        let len = rng.gen_range(0, 200);
+        let data: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+
+        let std = encode(&data, STANDARD);
+        assert_eq!(std, ::base64::encode(&data));
+        let url = encode(&data, URL_SAFE_NO_PAD);
+        let theirs = ::base64::encode_config(&data, ::base64::URL_SAFE_NO_PAD);
+        assert_eq!(url, theirs);
+        assert_eq!(decode(&std, STANDARD).unwrap(), data);
+        assert_eq!(decode(&url, URL_SAFE_NO_PAD).unwrap(), data);
+
+        // Streaming, in random pieces
+        let mut enc = Encoder::new(Vec::new(), STANDARD);
+        let mut rest = &data[..];
+        while !rest.is_empty() {
+            let n = rng.gen_range(1, 8).min(rest.len());
+            enc.write_all(&rest[..n]).unwrap();
+            rest = &rest[n..];
+        }
+        assert_eq!(enc.finish().unwrap(), std.as_bytes());
+        let max = rng.gen_range(1, 8);
+        let trickle = Trickle(std.as_bytes(), max);
+        let mut dec = Decoder::new(trickle, STANDARD, Mode::Strict);
+        let mut decoded = Vec::new();
+        dec.read_to_end(&mut decoded).unwrap();
+        assert_eq!(decoded, data);
+
+        // Whitespace and missing padding only in lenient mode
+        let mut spaced = String::new();
+        for c in std.trim_end_matches('=').chars() {
+            spaced.push(c);
+            if rng.gen_range(0, 10) == 0 {
+                spaced.push_str(if rng.gen() { "\n" } else { " " });
+            }
+        }
+        assert_eq!(decode_lenient(&spaced, STANDARD).unwrap(), data);
+        if spaced != std {
+            assert!(decode(&spaced, STANDARD).is_err());
+        }
+
+        // Unpadded garbage is accepted exactly when the crate accepts it.
+        // The crate is more forgiving with padding, any number of `=` goes.
+        let garbage: String = (0..rng.gen_range(0, 12))
+            .map(|_| STANDARD_ALPHABET[rng.gen_range(0, 64)] as char)
+            .collect();
+        assert_eq!(
+            decode_lenient(&garbage, STANDARD).ok(),
+            ::base64::decode(&garbage).ok(),
+            "{}",
+            garbage
+        );
+    }
+}
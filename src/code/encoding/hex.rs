@@ -0,0 +1,38 @@
+//! Base16, lowercase when encoding, either case when decoding
+
+use super::DecodeError;
+
+const ALPHABET: &[u8; 16] = b"0123456789abcdef";
+
+pub fn encode(inp: &[u8]) -> String {
+    let mut out = String::with_capacity(2 * inp.len());
+    for b in inp {
+        out.push(ALPHABET[(b >> 4) as usize] as char);
+        out.push(ALPHABET[(b & 0xf) as usize] as char);
+    }
+    out
+}
+
+fn nibble(offset: usize, c: u8) -> Result<u8, DecodeError> {
+    match c {
+        b'0'..=b'9' => Ok(c - b'0'),
+        b'a'..=b'f' => Ok(c - b'a' + 10),
+        b'A'..=b'F' => Ok(c - b'A' + 10),
+        _ => Err(DecodeError::InvalidByte { offset, byte: c }),
+    }
+}
+
+pub fn decode(inp: &str) -> Result<Vec<u8>, DecodeError> {
+    let inp = inp.as_bytes();
+    if !inp.len().is_multiple_of(2) {
+        return Err(DecodeError::InvalidLength(inp.len()));
+    }
+    inp.chunks_exact(2)
+        .enumerate()
+        .map(|(i, pair)| {
+            let hi = nibble(2 * i, pair[0])?;
+            let lo = nibble(2 * i + 1, pair[1])?;
+            Ok(hi << 4 | lo)
+        })
+        .collect()
+}
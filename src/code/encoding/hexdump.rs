@@ -0,0 +1,65 @@
+//! `xxd` style hexdumps, 16 bytes per line
+
+use super::DecodeError;
+
+const LINE: usize = 16;
+/// 8 groups of 4 hex digits, with spaces between
+const HEX_WIDTH: usize = 39;
+
+/// Printable ascii as is, everything else as `.`
+pub fn printable(b: u8) -> char {
+    if (0x20..=0x7e).contains(&b) {
+        b as char
+    } else {
+        '.'
+    }
+}
+
+/// Same as `xxd`, including the trailing newline
+pub fn encode(inp: &[u8]) -> String {
+    let mut out = String::new();
+    for (i, line) in inp.chunks(LINE).enumerate() {
+        let groups: Vec<_> = line.chunks(2).map(super::hex::encode).collect();
+        let ascii: String = line.iter().map(|b| printable(*b)).collect();
+        out += &format!(
+            "{:08x}: {:w$}  {}\n",
+            i * LINE,
+            groups.join(" "),
+            ascii,
+            w = HEX_WIDTH
+        );
+    }
+    out
+}
+
+/// Like `xxd -r`. Only the hex columns are read, offsets and ascii are
+/// ignored. Offsets of errors are per line.
+pub fn decode(inp: &str) -> Result<Vec<u8>, DecodeError> {
+    let mut out = Vec::new();
+    for line in inp.lines().filter(|l| !l.trim().is_empty()) {
+        let colon = line.find(": ").ok_or(DecodeError::InvalidPadding)?;
+        super::hex::decode(&line[..colon])?;
+        let rest = &line[(colon + 2)..];
+        // The ascii column starts after the first double space
+        let hex_col = match rest.find("  ") {
+            Some(end) => &rest[..end],
+            None => rest,
+        };
+        let hex: String = hex_col.split(' ').collect();
+        out.extend(super::hex::decode(&hex)?);
+    }
+    Ok(out)
+}
+
+#[test]
+fn test_like_xxd() {
+    let inp = b"Hello, world!\n0123456789abcdefXYZ";
+    let dump = concat!(
+        "00000000: 4865 6c6c 6f2c 2077 6f72 6c64 210a 3031  Hello, world!.01\n",
+        "00000010: 3233 3435 3637 3839 6162 6364 6566 5859  23456789abcdefXY\n",
+        "00000020: 5a                                       Z\n",
+    );
+    assert_eq!(encode(inp), dump);
+    assert_eq!(decode(dump).unwrap(), &inp[..]);
+    assert!(decode("00000000 4865").is_err());
+}
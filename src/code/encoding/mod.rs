@@ -0,0 +1,214 @@
+//! Text encodings of binary data.
+//!
+//! Unlike `code::decode_hex` and friends, decoders here return an error on
+//! bad input instead of panicking.
+
+use std::fmt;
+
+pub mod ascii85;
+pub mod base32;
+pub mod base58;
+pub mod base64;
+pub mod hex;
+pub mod hexdump;
+pub mod percent;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// `byte` at `offset` is not part of the encoding
+    InvalidByte { offset: usize, byte: u8 },
+    /// No input of this length could have come from the encoder
+    InvalidLength(usize),
+    /// Padding missing, misplaced or where none is allowed
+    InvalidPadding,
+    /// The group at `offset` decodes to more than fits
+    Overflow { offset: usize },
+}
+
+impl std::error::Error for DecodeError {}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::InvalidByte { offset, byte } => {
+                write!(f, "Invalid byte 0x{:02x} at {}", byte, offset)
+            }
+            DecodeError::InvalidLength(len) => {
+                write!(f, "Invalid length {}", len)
+            }
+            DecodeError::InvalidPadding => write!(f, "Invalid padding"),
+            DecodeError::Overflow { offset } => {
+                write!(f, "Group at {} overflows", offset)
+            }
+        }
+    }
+}
+
+/// byte -> index in `alphabet`, 0xff if not in it
+fn lookup(alphabet: &[u8]) -> [u8; 256] {
+    let mut table = [0xff; 256];
+    for (i, c) in alphabet.iter().enumerate() {
+        table[*c as usize] = i as u8;
+    }
+    table
+}
+
+/// Every `bits` bits of `inp` as a char from `alphabet`, the last one
+/// padded with 0 bits. No padding chars.
+fn pack(inp: &[u8], alphabet: &[u8], bits: u32) -> String {
+    let mask = (1 << bits) - 1;
+    let len = (inp.len() * 8).div_ceil(bits as usize);
+    let mut out = String::with_capacity(len);
+    let mut acc = 0u32;
+    let mut nacc = 0;
+    for b in inp {
+        acc = (acc << 8) | *b as u32;
+        nacc += 8;
+        while nacc >= bits {
+            nacc -= bits;
+            out.push(alphabet[((acc >> nacc) & mask) as usize] as char);
+        }
+    }
+    if nacc > 0 {
+        out.push(alphabet[((acc << (bits - nacc)) & mask) as usize] as char);
+    }
+    out
+}
+
+/// Inverse of `pack`. Left over bits must be fewer than `bits` and zero.
+fn unpack(
+    inp: &[u8],
+    alphabet: &[u8],
+    bits: u32,
+) -> Result<Vec<u8>, DecodeError> {
+    let table = lookup(alphabet);
+    let mut out = Vec::with_capacity(inp.len() * bits as usize / 8);
+    let mut acc = 0u32;
+    let mut nacc = 0;
+    for (offset, c) in inp.iter().enumerate() {
+        let v = table[*c as usize];
+        if v == 0xff {
+            return Err(DecodeError::InvalidByte { offset, byte: *c });
+        }
+        acc = (acc << bits) | v as u32;
+        nacc += bits;
+        if nacc >= 8 {
+            nacc -= 8;
+            out.push((acc >> nacc) as u8);
+        }
+    }
+    if nacc >= bits {
+        return Err(DecodeError::InvalidLength(inp.len()));
+    }
+    if acc & ((1 << nacc) - 1) != 0 {
+        let offset = inp.len() - 1;
+        return Err(DecodeError::InvalidByte {
+            offset,
+            byte: inp[offset],
+        });
+    }
+    Ok(out)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    Hexdump,
+    Hex,
+    Base32,
+    Base64,
+    Base64Url,
+    Base58,
+    Ascii85,
+    Percent,
+}
+
+pub const ALL: &[Encoding] = &[
+    Encoding::Hexdump,
+    Encoding::Hex,
+    Encoding::Base32,
+    Encoding::Base64,
+    Encoding::Base64Url,
+    Encoding::Base58,
+    Encoding::Ascii85,
+    Encoding::Percent,
+];
+
+impl Encoding {
+    pub fn name(self) -> &'static str {
+        match self {
+            Encoding::Hexdump => "hexdump",
+            Encoding::Hex => "hex",
+            Encoding::Base32 => "base32",
+            Encoding::Base64 => "base64",
+            Encoding::Base64Url => "base64url",
+            Encoding::Base58 => "base58",
+            Encoding::Ascii85 => "ascii85",
+            Encoding::Percent => "percent",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        ALL.iter().copied().find(|e| e.name() == name)
+    }
+
+    /// Whitespace is ignored, except in hexdumps and percent-encoding where
+    /// it is part of the format
+    pub fn decode(self, inp: &str) -> Result<Vec<u8>, DecodeError> {
+        let compact = || -> String {
+            inp.chars().filter(|c| !c.is_whitespace()).collect()
+        };
+        match self {
+            Encoding::Hexdump => hexdump::decode(inp),
+            Encoding::Hex => hex::decode(&compact()),
+            Encoding::Base32 => base32::decode(&compact()),
+            Encoding::Base64 => base64::decode_lenient(inp, base64::STANDARD),
+            Encoding::Base64Url => {
+                base64::decode_lenient(inp, base64::URL_SAFE)
+            }
+            Encoding::Base58 => base58::decode(&compact()),
+            Encoding::Ascii85 => ascii85::decode(inp),
+            Encoding::Percent => percent::decode(inp.trim_end()),
+        }
+    }
+}
+
+/// Every encoding `inp` decodes as, the most specific first.
+/// Eg "cafe" is hex but also base64 and base58.
+pub fn candidates(inp: &str) -> Vec<Encoding> {
+    ALL.iter()
+        .copied()
+        .filter(|e| match e {
+            // Anything without a % would be valid but pointless
+            Encoding::Percent => inp.contains('%'),
+            // Don't confuse plain base64 with url-safe
+            Encoding::Base64Url => inp.contains('-') || inp.contains('_'),
+            _ => true,
+        })
+        .filter(|e| !inp.trim().is_empty() && e.decode(inp).is_ok())
+        .collect()
+}
+
+/// The most likely encoding of `inp`
+pub fn detect(inp: &str) -> Option<Encoding> {
+    candidates(inp).first().copied()
+}
+
+#[test]
+fn test_detect() {
+    let data = b"Some bytes \x00\xff to encode, long enough to tell apart";
+    let encoded = [
+        (Encoding::Hexdump, hexdump::encode(data)),
+        (Encoding::Hex, hex::encode(data)),
+        (Encoding::Base32, base32::encode(data)),
+        (Encoding::Base64, base64::encode(data, base64::STANDARD)),
+        (Encoding::Base58, base58::encode(data)),
+        (Encoding::Ascii85, ascii85::encode(data)),
+        (Encoding::Percent, percent::encode_component(data)),
+    ];
+    for (enc, s) in encoded.iter() {
+        assert_eq!(detect(s), Some(*enc), "{}", s);
+        assert_eq!(enc.decode(s).unwrap(), &data[..]);
+    }
+    assert_eq!(detect("-_-_"), Some(Encoding::Base64Url));
+    assert_eq!(detect(" "), None);
+}
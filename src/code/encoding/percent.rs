@@ -0,0 +1,80 @@
+//! Percent-encoding (URL-encoding) as in RFC 3986. `+` is just a `+`.
+
+use super::DecodeError;
+
+/// The chars that never need encoding
+fn unreserved(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"-._~".contains(&b)
+}
+
+fn encode_if<F>(inp: &[u8], needs: F) -> String
+where
+    F: Fn(u8) -> bool,
+{
+    let mut out = String::with_capacity(inp.len());
+    for b in inp {
+        if needs(*b) {
+            out += &format!("%{:02X}", b);
+        } else {
+            out.push(*b as char);
+        }
+    }
+    out
+}
+
+/// Encodes everything except unreserved chars, for a single path segment
+/// or query value
+pub fn encode_component(inp: &[u8]) -> String {
+    encode_if(inp, |b| !unreserved(b))
+}
+
+/// Encodes `%`, space, non-printable or non-ascii bytes and anything in
+/// `reserved`. Eg `encode(userdata, b";=")` to keep it from adding fields
+/// to a `;` separated cookie.
+pub fn encode(inp: &[u8], reserved: &[u8]) -> String {
+    encode_if(inp, |b| {
+        b == b'%' || !b.is_ascii_graphic() || reserved.contains(&b)
+    })
+}
+
+pub fn decode(inp: &str) -> Result<Vec<u8>, DecodeError> {
+    let inp = inp.as_bytes();
+    let mut out = Vec::with_capacity(inp.len());
+    let mut i = 0;
+    while i < inp.len() {
+        let b = inp[i];
+        if b == b'%' {
+            let hex = inp
+                .get((i + 1)..(i + 3))
+                .ok_or(DecodeError::InvalidLength(inp.len()))?;
+            let hex = std::str::from_utf8(hex)
+                .map_err(|_| DecodeError::InvalidByte { offset: i, byte: b })?;
+            let v = super::hex::decode(hex).map_err(|_| {
+                DecodeError::InvalidByte { offset: i, byte: b }
+            })?;
+            out.extend(v);
+            i += 3;
+        } else if b.is_ascii_graphic() {
+            out.push(b);
+            i += 1;
+        } else {
+            return Err(DecodeError::InvalidByte { offset: i, byte: b });
+        }
+    }
+    Ok(out)
+}
+
+#[test]
+fn test_roundtrip() {
+    let inp = b"comment1=cooking MCs;admin=true;\x00\xff%";
+    assert_eq!(
+        encode(inp, b";="),
+        "comment1%3Dcooking%20MCs%3Badmin%3Dtrue%3B%00%FF%25"
+    );
+    assert_eq!(encode_component(b"a-b_c d/e"), "a-b_c%20d%2Fe");
+    assert_eq!(decode(&encode(inp, b"")).unwrap(), &inp[..]);
+    assert_eq!(decode(&encode_component(inp)).unwrap(), &inp[..]);
+    assert!(decode("%4").is_err());
+    assert!(decode("%zz").is_err());
+    assert!(decode("a b").is_err());
+}
@@ -0,0 +1,188 @@
+//! Linear algebra over GF(2).
+//!
+//! Used for cracking generators whose outputs are linear functions of their
+//! state bits (like MT19937 and xorshift).
+
+/// A vector of bits, packed 64 to a word.
+/// Bit `i` is bit `i % 64` of word `i / 64`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitRow {
+    len: usize,
+    words: Vec<u64>,
+}
+
+impl BitRow {
+    pub fn zero(len: usize) -> Self {
+        let words = vec![0; len.div_ceil(64)];
+        Self { len, words }
+    }
+
+    /// All zeros except bit `i`
+    pub fn unit(len: usize, i: usize) -> Self {
+        let mut row = Self::zero(len);
+        row.set(i, true);
+        row
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, i: usize) -> bool {
+        assert!(i < self.len);
+        (self.words[i / 64] >> (i % 64)) & 1 == 1
+    }
+
+    pub fn set(&mut self, i: usize, b: bool) {
+        assert!(i < self.len);
+        let one = 1u64 << (i % 64);
+        if b {
+            self.words[i / 64] |= one;
+        } else {
+            self.words[i / 64] &= !one;
+        }
+    }
+
+    pub fn xor_with(&mut self, other: &BitRow) {
+        self.xor_from(other, 0);
+    }
+
+    /// Like `xor_with` but skips the first `word` words, for when `other` is
+    /// known to be zero there.
+    fn xor_from(&mut self, other: &BitRow, word: usize) {
+        assert!(self.len == other.len);
+        for (a, b) in self.words[word..].iter_mut().zip(&other.words[word..]) {
+            *a ^= b;
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    /// Index of the lowest set bit with index `>= from`
+    fn first_one_from(&self, from: usize) -> Option<usize> {
+        if from >= self.len {
+            return None;
+        }
+        let first = from / 64;
+        let masked = self.words[first] & (!0u64 << (from % 64));
+        if masked != 0 {
+            return Some(first * 64 + masked.trailing_zeros() as usize);
+        }
+        self.words[(first + 1)..]
+            .iter()
+            .position(|w| *w != 0)
+            .map(|j| {
+                let wi = first + 1 + j;
+                wi * 64 + self.words[wi].trailing_zeros() as usize
+            })
+    }
+
+    /// Parity of the bitwise and of `self` and `other`,
+    /// ie the dot product over GF(2).
+    pub fn dot(&self, other: &BitRow) -> bool {
+        assert!(self.len == other.len);
+        let ones: u32 = self
+            .words
+            .iter()
+            .zip(&other.words)
+            .map(|(a, b)| (a & b).count_ones())
+            .sum();
+        ones % 2 == 1
+    }
+}
+
+/// A system of linear equations over GF(2) that is built one equation at a
+/// time.
+///
+/// Equations are reduced as they are added, so the system is always in
+/// echelon form: every stored row has a distinct leading (lowest) variable.
+pub struct LinearSystem {
+    vars: usize,
+    /// `pivots[v]` is the row (and right hand side) whose lowest variable
+    /// is `v`, if any.
+    pivots: Vec<Option<(BitRow, bool)>>,
+    rank: usize,
+    consistent: bool,
+}
+
+impl LinearSystem {
+    pub fn new(vars: usize) -> Self {
+        Self {
+            vars,
+            pivots: vec![None; vars],
+            rank: 0,
+            consistent: true,
+        }
+    }
+
+    pub fn vars(&self) -> usize {
+        self.vars
+    }
+
+    pub fn rank(&self) -> usize {
+        self.rank
+    }
+
+    /// Number of variables not pinned down by the equations so far
+    pub fn free_vars(&self) -> usize {
+        self.vars - self.rank
+    }
+
+    /// False if some added equation contradicted the earlier ones
+    pub fn is_consistent(&self) -> bool {
+        self.consistent
+    }
+
+    /// Adds the equation `row . x = rhs`.
+    /// Returns true if the equation was new information, false if it already
+    /// followed from (or contradicted) the earlier equations.
+    pub fn add(&mut self, mut row: BitRow, mut rhs: bool) -> bool {
+        assert!(row.len() == self.vars);
+        let mut from = 0;
+        while let Some(v) = row.first_one_from(from) {
+            match &self.pivots[v] {
+                Some((pivot, pivot_rhs)) => {
+                    // Pivot has nothing below v, so neither do earlier words
+                    row.xor_from(pivot, v / 64);
+                    rhs ^= pivot_rhs;
+                    from = v + 1;
+                }
+                None => {
+                    self.pivots[v] = Some((row, rhs));
+                    self.rank += 1;
+                    return true;
+                }
+            }
+        }
+        // Reduced to 0 = rhs
+        if rhs {
+            self.consistent = false;
+        }
+        false
+    }
+
+    /// Returns a solution, with all free variables set to 0.
+    /// None if the system is inconsistent.
+    pub fn solve(&self) -> Option<BitRow> {
+        if !self.consistent {
+            return None;
+        }
+        let mut x = BitRow::zero(self.vars);
+        // Each pivot row only involves its own variable and higher ones, so
+        // going from the top down every other variable in the row is known.
+        for v in (0..self.vars).rev() {
+            if let Some((row, rhs)) = &self.pivots[v] {
+                // x[v] is still 0 so it doesn't contribute to the dot product
+                let b = rhs ^ row.dot(&x);
+                x.set(v, b);
+            }
+        }
+        Some(x)
+    }
+}
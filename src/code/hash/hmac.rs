@@ -0,0 +1,142 @@
+//! HMAC, RFC 2104, over any of the hashes
+
+use super::{Hasher, MerkleDamgard};
+
+#[derive(Clone)]
+pub struct Hmac<H: MerkleDamgard> {
+    inner: Hasher<H>,
+    /// The key xor opad, what the outer hash starts with
+    outer_key: Vec<u8>,
+}
+
+impl<H: MerkleDamgard> Hmac<H> {
+    /// Keys longer than a block are hashed first
+    pub fn new(key: &[u8]) -> Self {
+        let mut key = if key.len() > H::BLOCKSIZE {
+            Hasher::<H>::digest(key).as_ref().to_vec()
+        } else {
+            key.to_vec()
+        };
+        key.resize(H::BLOCKSIZE, 0);
+        let inner_key: Vec<u8> = key.iter().map(|b| b ^ 0x36).collect();
+        let mut inner = Hasher::new();
+        inner.update(&inner_key);
+        Self {
+            inner,
+            outer_key: key.iter().map(|b| b ^ 0x5c).collect(),
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    pub fn finalize(self) -> H::Digest {
+        let inner = self.inner.finalize();
+        let mut outer = Hasher::<H>::new();
+        outer.update(&self.outer_key);
+        outer.update(inner.as_ref());
+        outer.finalize()
+    }
+
+    pub fn mac(key: &[u8], msg: &[u8]) -> H::Digest {
+        let mut h = Self::new(key);
+        h.update(msg);
+        h.finalize()
+    }
+
+    /// Checks `tag` in constant time
+    pub fn verify(key: &[u8], msg: &[u8], tag: &[u8]) -> bool {
+        constant_time_eq(Self::mac(key, msg).as_ref(), tag)
+    }
+}
+
+/// Looks at every byte no matter where the first difference is, unlike
+/// `code::cmp_blocks`. Only the length can leak.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[test]
+fn test_rfc2202_rfc4231() {
+    use super::{md5, sha1, sha256, sha512};
+    use crate::code::encode_hex;
+
+    fn check<H: MerkleDamgard>(keylens: &[usize], expected: &[&str]) {
+        let (hi, jefe) = (b"Hi There", b"what do ya want for nothing?");
+        let long = b"Test Using Larger Than Block-Size Key - Hash Key First";
+        let cases: [(Vec<u8>, &[u8]); 3] = [
+            (vec![0x0b; keylens[0]], hi),
+            (b"Jefe".to_vec(), jefe),
+            (vec![0xaa; keylens[1]], long),
+        ];
+        for ((key, msg), hex) in cases.iter().zip(expected) {
+            assert_eq!(encode_hex(Hmac::<H>::mac(key, msg).as_ref()), *hex);
+            assert!(Hmac::<H>::verify(
+                key,
+                msg,
+                Hmac::<H>::mac(key, msg).as_ref()
+            ));
+        }
+    }
+
+    // RFC 2202
+    check::<md5::Md5Core>(
+        &[16, 80],
+        &[
+            "9294727a3638bb1c13f48ef8158bfc9d",
+            "750c783e6ab0b503eaa86e310a5db738",
+            "6b1ab7fe4bd7bf8f0b62e6ce61b9d0cd",
+        ],
+    );
+    check::<sha1::Sha1Core>(
+        &[20, 80],
+        &[
+            "b617318655057264e28bc0b6fb378c8ef146be00",
+            "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79",
+            "aa4ae5e15272d00e95705637ce8a3b55ed402112",
+        ],
+    );
+    // RFC 4231
+    check::<sha256::Sha224Core>(
+        &[20, 131],
+        &[
+            "896fb1128abbdf196832107cd49df33f47b4b1169912ba4f53684b22",
+            "a30e01098bc6dbbf45690f3a7e9e6d0f8bbea2a39e6148008fd05e44",
+            "95e9a0db962095adaebe9b2d6f0dbce2d499f112f2d2b7273fa6870e",
+        ],
+    );
+    check::<sha256::Sha256Core>(
+        &[20, 131],
+        &[
+            "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+        ],
+    );
+    check::<sha512::Sha384Core>(
+        &[20, 131],
+        &[
+            "afd03944d84895626b0825f4ab46907f15f9dadbe4101ec6\
+             82aa034c7cebc59cfaea9ea9076ede7f4af152e8b2fa9cb6",
+            "af45d2e376484031617f78d2b58a6b1b9c7ef464f5a01b47\
+             e42ec3736322445e8e2240ca5e69e2c78b3239ecfab21649",
+            "4ece084485813e9088d2c63a041bc5b44f9ef1012a2b588f\
+             3cd11f05033ac4c60c2ef6ab4030fe8296248df163f44952",
+        ],
+    );
+    check::<sha512::Sha512Core>(
+        &[20, 131],
+        &[
+            "87aa7cdea5ef619d4ff0b4241a1d6cb02379f4e2ce4ec2787ad0b30545e17cde\
+             daa833b7d6b8a702038b274eaea3f4e4be9d914eeb61f1702e696c203a126854",
+            "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea250554\
+             9758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737",
+            "80b24263c7c1a3ebb71493c1dd7be8b49b46d1f41b4aeec1121b013783f8f352\
+             6b56d037e05f2598bd0fd2215d6a1e5295e64f73f63f0aec8b915a985d786598",
+        ],
+    );
+    assert!(!constant_time_eq(b"abc", b"abd"));
+    assert!(!constant_time_eq(b"abc", b"ab"));
+}
@@ -0,0 +1,108 @@
+//! MD4, RFC 1320
+
+use super::{words, Hasher, MerkleDamgard};
+
+use std::convert::TryInto;
+
+pub const BLOCKSIZE: usize = 64;
+pub const OUTPUT: usize = 16;
+
+pub type State = [u32; 4];
+
+pub const IV: State = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+/// (constant, message word order, shifts) of each round
+const ROUNDS: [(u32, [usize; 16], [u32; 4]); 3] = [
+    (
+        0,
+        [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+        [3, 7, 11, 19],
+    ),
+    (
+        0x5a827999,
+        [0, 4, 8, 12, 1, 5, 9, 13, 2, 6, 10, 14, 3, 7, 11, 15],
+        [3, 5, 9, 13],
+    ),
+    (
+        0x6ed9eba1,
+        [0, 8, 4, 12, 2, 10, 6, 14, 1, 9, 5, 13, 3, 11, 7, 15],
+        [3, 9, 11, 15],
+    ),
+];
+
+/// One application of the compression function
+pub fn compress(state: &mut State, block: &[u8; BLOCKSIZE]) {
+    let mut x = [0u32; 16];
+    let words = words(block, u32::from_le_bytes);
+    x.iter_mut().zip(words).for_each(|(x, w)| *x = w);
+
+    let mut v = *state;
+    for (round, (k, order, shifts)) in ROUNDS.iter().enumerate() {
+        for (j, w) in order.iter().enumerate() {
+            // Updates a, d, c, b, a, ... with the other three as arguments
+            let r = (4 - j % 4) % 4;
+            let (b, c, d) = (v[(r + 1) % 4], v[(r + 2) % 4], v[(r + 3) % 4]);
+            let f = match round {
+                0 => (b & c) | (!b & d),
+                1 => (b & c) | (b & d) | (c & d),
+                _ => b ^ c ^ d,
+            };
+            v[r] = v[r]
+                .wrapping_add(f)
+                .wrapping_add(x[*w])
+                .wrapping_add(*k)
+                .rotate_left(shifts[j % 4]);
+        }
+    }
+    for (s, x) in state.iter_mut().zip(&v) {
+        *s = s.wrapping_add(*x);
+    }
+}
+
+/// Marker for `Hasher`
+#[derive(Clone, Copy)]
+pub struct Md4Core;
+
+impl MerkleDamgard for Md4Core {
+    type State = State;
+    type Digest = [u8; OUTPUT];
+
+    const BLOCKSIZE: usize = BLOCKSIZE;
+    const IV: State = IV;
+    const LENGTH_BYTES: usize = 8;
+    const BIG_ENDIAN: bool = false;
+
+    fn compress(state: &mut State, block: &[u8]) {
+        compress(state, block.try_into().unwrap());
+    }
+
+    fn output(state: &State) -> [u8; OUTPUT] {
+        let mut out = [0; OUTPUT];
+        for (o, s) in out.chunks_exact_mut(4).zip(state) {
+            o.copy_from_slice(&s.to_le_bytes());
+        }
+        out
+    }
+
+    fn state_from_digest(digest: &[u8; OUTPUT]) -> Option<State> {
+        let mut state = [0; 4];
+        let digest = words(digest, u32::from_le_bytes);
+        state.iter_mut().zip(digest).for_each(|(s, w)| *s = w);
+        Some(state)
+    }
+}
+
+pub type Md4 = Hasher<Md4Core>;
+
+#[test]
+fn test_rfc1320() {
+    let hex = |data: &[u8]| crate::code::encode_hex(&Md4::digest(data));
+    assert_eq!(hex(b""), "31d6cfe0d16ae931b73c59d7e0c089c0");
+    assert_eq!(hex(b"abc"), "a448017aaf21d8525fc10ae87aa6729d");
+    assert_eq!(
+        hex(b"abcdefghijklmnopqrstuvwxyz"),
+        "d79e1c308aa5bbcdeea8ed63df412da9"
+    );
+    let digits = b"1234567890".repeat(8);
+    assert_eq!(hex(&digits), "e33b4ddc9c38f2199c3e7b164fcc0536");
+}
@@ -0,0 +1,104 @@
+//! MD5, RFC 1321
+
+use super::{words, Hasher, MerkleDamgard};
+
+use lazy_static::*;
+use std::convert::TryInto;
+
+pub const BLOCKSIZE: usize = 64;
+pub const OUTPUT: usize = 16;
+
+pub type State = [u32; 4];
+
+pub const IV: State = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+const SHIFTS: [[u32; 4]; 4] = [
+    [7, 12, 17, 22],
+    [5, 9, 14, 20],
+    [4, 11, 16, 23],
+    [6, 10, 15, 21],
+];
+
+lazy_static! {
+    /// floor(2^32 * abs(sin(i + 1)))
+    static ref K: Vec<u32> = (0..64)
+        .map(|i| (((i + 1) as f64).sin().abs() * 4294967296.0) as u32)
+        .collect();
+}
+
+/// One application of the compression function
+pub fn compress(state: &mut State, block: &[u8; BLOCKSIZE]) {
+    let mut m = [0u32; 16];
+    let words = words(block, u32::from_le_bytes);
+    m.iter_mut().zip(words).for_each(|(x, w)| *x = w);
+
+    let [mut a, mut b, mut c, mut d] = *state;
+    for i in 0..64 {
+        let (f, g) = match i / 16 {
+            0 => ((b & c) | (!b & d), i),
+            1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+            2 => (b ^ c ^ d, (3 * i + 5) % 16),
+            _ => (c ^ (b | !d), (7 * i) % 16),
+        };
+        let t = a
+            .wrapping_add(f)
+            .wrapping_add(K[i])
+            .wrapping_add(m[g])
+            .rotate_left(SHIFTS[i / 16][i % 4]);
+        a = d;
+        d = c;
+        c = b;
+        b = b.wrapping_add(t);
+    }
+    for (s, x) in state.iter_mut().zip(&[a, b, c, d]) {
+        *s = s.wrapping_add(*x);
+    }
+}
+
+/// Marker for `Hasher`
+#[derive(Clone, Copy)]
+pub struct Md5Core;
+
+impl MerkleDamgard for Md5Core {
+    type State = State;
+    type Digest = [u8; OUTPUT];
+
+    const BLOCKSIZE: usize = BLOCKSIZE;
+    const IV: State = IV;
+    const LENGTH_BYTES: usize = 8;
+    const BIG_ENDIAN: bool = false;
+
+    fn compress(state: &mut State, block: &[u8]) {
+        compress(state, block.try_into().unwrap());
+    }
+
+    fn output(state: &State) -> [u8; OUTPUT] {
+        let mut out = [0; OUTPUT];
+        for (o, s) in out.chunks_exact_mut(4).zip(state) {
+            o.copy_from_slice(&s.to_le_bytes());
+        }
+        out
+    }
+
+    fn state_from_digest(digest: &[u8; OUTPUT]) -> Option<State> {
+        let mut state = [0; 4];
+        let digest = words(digest, u32::from_le_bytes);
+        state.iter_mut().zip(digest).for_each(|(s, w)| *s = w);
+        Some(state)
+    }
+}
+
+pub type Md5 = Hasher<Md5Core>;
+
+#[test]
+fn test_rfc1321() {
+    let hex = |data: &[u8]| crate::code::encode_hex(&Md5::digest(data));
+    assert_eq!(hex(b""), "d41d8cd98f00b204e9800998ecf8427e");
+    assert_eq!(hex(b"abc"), "900150983cd24fb0d6963f7d28e17f72");
+    assert_eq!(
+        hex(b"abcdefghijklmnopqrstuvwxyz"),
+        "c3fcd3d76192e4007dfb496cca67e13b"
+    );
+    let digits = b"1234567890".repeat(8);
+    assert_eq!(hex(&digits), "57edf4a22be3c955ac49da2e2107b67a");
+}
@@ -0,0 +1,229 @@
+//! Hash functions, written out so their internals can be poked at.
+//!
+//! Every hash here is a Merkle–Damgård construction: a compression
+//! function run over padded blocks, the state after the last block being
+//! the digest. Anyone with a digest can therefore carry on hashing from it,
+//! which is the length extension attack. Truncated variants don't give
+//! away the whole state and are immune.
+
+pub mod hmac;
+pub mod md4;
+pub mod md5;
+pub mod sha1;
+pub mod sha256;
+pub mod sha512;
+pub use hmac::Hmac;
+pub use md4::Md4;
+pub use md5::Md5;
+pub use sha1::Sha1;
+pub use sha256::{Sha224, Sha256};
+pub use sha512::{Sha384, Sha512, Sha512_256};
+
+use std::convert::TryInto;
+
+/// The parts that differ between hashes. Implemented by marker types, the
+/// hashing itself is done by `Hasher`.
+pub trait MerkleDamgard {
+    /// The chaining registers
+    type State: Copy;
+    type Digest: AsRef<[u8]>;
+
+    const BLOCKSIZE: usize;
+    const IV: Self::State;
+    /// Bytes of the message length at the end of the padding
+    const LENGTH_BYTES: usize;
+    /// Whether the length, and every word, is big endian
+    const BIG_ENDIAN: bool;
+
+    /// One application of the compression function to a whole block
+    fn compress(state: &mut Self::State, block: &[u8]);
+
+    fn output(state: &Self::State) -> Self::Digest;
+
+    /// The state registers a digest was read out from. `None` if the
+    /// digest is truncated.
+    fn state_from_digest(digest: &Self::Digest) -> Option<Self::State>;
+
+    /// What gets appended to a message of `len` bytes before hashing: 0x80,
+    /// zeros and the length in bits, up to a whole number of blocks
+    fn glue_padding(len: u64) -> Vec<u8> {
+        let (bs, lb) = (Self::BLOCKSIZE, Self::LENGTH_BYTES);
+        let used = (len % bs as u64) as usize + 1 + lb;
+        let mut pad = vec![0x80];
+        pad.extend(vec![0; (bs - used % bs) % bs]);
+        let bits = len as u128 * 8;
+        if Self::BIG_ENDIAN {
+            pad.extend(&bits.to_be_bytes()[16 - lb..]);
+        } else {
+            pad.extend(&bits.to_le_bytes()[..lb]);
+        }
+        pad
+    }
+}
+
+#[derive(Clone)]
+pub struct Hasher<H: MerkleDamgard> {
+    state: H::State,
+    /// Bytes hashed so far, including what's in `buf`
+    len: u64,
+    buf: Vec<u8>,
+}
+
+impl<H: MerkleDamgard> Hasher<H> {
+    pub fn new() -> Self {
+        Self::from_state(H::IV, 0)
+    }
+
+    /// Continues from `state` after `len` bytes, which must be a whole
+    /// number of blocks. Eg from a digest and the length of the message
+    /// plus its glue padding.
+    pub fn from_state(state: H::State, len: u64) -> Self {
+        assert!(len.is_multiple_of(H::BLOCKSIZE as u64));
+        Self {
+            state,
+            len,
+            buf: Vec::with_capacity(H::BLOCKSIZE),
+        }
+    }
+
+    /// `from_state` with the state read out of `digest`
+    pub fn resume(digest: &H::Digest, len: u64) -> Option<Self> {
+        H::state_from_digest(digest).map(|s| Self::from_state(s, len))
+    }
+
+    pub fn state(&self) -> H::State {
+        self.state
+    }
+
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        let bs = H::BLOCKSIZE;
+        self.len += data.len() as u64;
+        let mut data = data;
+        if !self.buf.is_empty() {
+            let take = (bs - self.buf.len()).min(data.len());
+            self.buf.extend(&data[..take]);
+            data = &data[take..];
+            if self.buf.len() < bs {
+                return;
+            }
+            H::compress(&mut self.state, &self.buf);
+            self.buf.clear();
+        }
+        let mut blocks = data.chunks_exact(bs);
+        for block in &mut blocks {
+            H::compress(&mut self.state, block);
+        }
+        self.buf.extend(blocks.remainder());
+    }
+
+    pub fn finalize(mut self) -> H::Digest {
+        let pad = H::glue_padding(self.len);
+        self.update(&pad);
+        assert!(self.buf.is_empty());
+        H::output(&self.state)
+    }
+
+    pub fn digest(data: &[u8]) -> H::Digest {
+        let mut h = Self::new();
+        h.update(data);
+        h.finalize()
+    }
+}
+
+impl<H: MerkleDamgard> Default for Hasher<H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Words of `bytes` in the hash's byte order
+fn words<'a, W: 'a, const N: usize>(
+    bytes: &'a [u8],
+    from: fn([u8; N]) -> W,
+) -> impl Iterator<Item = W> + 'a {
+    bytes
+        .chunks_exact(N)
+        .map(move |w| from(w.try_into().unwrap()))
+}
+
+/// A message with a valid MAC that was never asked for
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Forgery {
+    /// Length of the secret prefix that made it verify
+    pub keylen: usize,
+    /// Original message, glue padding and the extension
+    pub message: Vec<u8>,
+    pub mac: Vec<u8>,
+}
+
+/// Appends `suffix` to `msg` given `mac = H(secret || msg)`, guessing the
+/// secret length up to `max_keylen` with `verify(message, mac)`.
+/// `None` if no guess verifies, or the hash is truncated.
+pub fn length_extension<H, F>(
+    msg: &[u8],
+    mac: &H::Digest,
+    suffix: &[u8],
+    max_keylen: usize,
+    verify: F,
+) -> Option<Forgery>
+where
+    H: MerkleDamgard,
+    F: Fn(&[u8], &[u8]) -> bool,
+{
+    let state = H::state_from_digest(mac)?;
+    (0..=max_keylen).find_map(|keylen| {
+        let total = (keylen + msg.len()) as u64;
+        let pad = H::glue_padding(total);
+        let mut h = Hasher::<H>::from_state(state, total + pad.len() as u64);
+        h.update(suffix);
+        let mac = h.finalize().as_ref().to_vec();
+        let message = [msg, &pad, suffix].concat();
+        if verify(&message, &mac) {
+            Some(Forgery {
+                keylen,
+                message,
+                mac,
+            })
+        } else {
+            None
+        }
+    })
+}
+
+/// Forges against a `H(secret || msg)` MAC with every hash, truncated ones
+/// should fail
+#[test]
+fn test_length_extension() {
+    fn attempt<H: MerkleDamgard>() -> bool {
+        let secret = b"legacy shared secret";
+        let mac = |m: &[u8]| Hasher::<H>::digest(&[&secret[..], m].concat());
+        let verify = |m: &[u8], t: &[u8]| mac(m).as_ref() == t;
+        let msg = b"comment1=cooking%20MCs;userdata=foo";
+        let suffix = b";admin=true";
+        match length_extension::<H, _>(msg, &mac(msg), suffix, 64, verify) {
+            Some(forged) => {
+                assert_eq!(forged.keylen, secret.len());
+                assert!(forged.message.ends_with(suffix));
+                assert_eq!(forged.mac, mac(&forged.message).as_ref());
+                true
+            }
+            None => false,
+        }
+    }
+    assert!(attempt::<md4::Md4Core>());
+    assert!(attempt::<md5::Md5Core>());
+    assert!(attempt::<sha1::Sha1Core>());
+    assert!(attempt::<sha256::Sha256Core>());
+    assert!(attempt::<sha512::Sha512Core>());
+    assert!(!attempt::<sha256::Sha224Core>());
+    assert!(!attempt::<sha512::Sha384Core>());
+    assert!(!attempt::<sha512::Sha512_256Core>());
+}
@@ -0,0 +1,100 @@
+//! SHA-1, FIPS 180-4
+
+use super::{words, Hasher, MerkleDamgard};
+
+use std::convert::TryInto;
+
+pub const BLOCKSIZE: usize = 64;
+pub const OUTPUT: usize = 20;
+
+pub type State = [u32; 5];
+
+pub const IV: State =
+    [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+/// One application of the compression function
+pub fn compress(state: &mut State, block: &[u8; BLOCKSIZE]) {
+    let mut w = [0u32; 80];
+    let words = words(block, u32::from_be_bytes);
+    w.iter_mut().zip(words).for_each(|(x, w)| *x = w);
+    for i in 16..80 {
+        w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e] = *state;
+    for (i, wi) in w.iter().enumerate() {
+        let (f, k) = match i {
+            0..=19 => ((b & c) | (!b & d), 0x5A827999),
+            20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+            40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+            _ => (b ^ c ^ d, 0xCA62C1D6),
+        };
+        let t = a
+            .rotate_left(5)
+            .wrapping_add(f)
+            .wrapping_add(e)
+            .wrapping_add(k)
+            .wrapping_add(*wi);
+        e = d;
+        d = c;
+        c = b.rotate_left(30);
+        b = a;
+        a = t;
+    }
+    for (s, x) in state.iter_mut().zip(&[a, b, c, d, e]) {
+        *s = s.wrapping_add(*x);
+    }
+}
+
+/// Marker for `Hasher`
+#[derive(Clone, Copy)]
+pub struct Sha1Core;
+
+impl MerkleDamgard for Sha1Core {
+    type State = State;
+    type Digest = [u8; OUTPUT];
+
+    const BLOCKSIZE: usize = BLOCKSIZE;
+    const IV: State = IV;
+    const LENGTH_BYTES: usize = 8;
+    const BIG_ENDIAN: bool = true;
+
+    fn compress(state: &mut State, block: &[u8]) {
+        compress(state, block.try_into().unwrap());
+    }
+
+    fn output(state: &State) -> [u8; OUTPUT] {
+        let mut out = [0; OUTPUT];
+        for (o, s) in out.chunks_exact_mut(4).zip(state) {
+            o.copy_from_slice(&s.to_be_bytes());
+        }
+        out
+    }
+
+    fn state_from_digest(digest: &[u8; OUTPUT]) -> Option<State> {
+        let mut state = [0; 5];
+        let digest = words(digest, u32::from_be_bytes);
+        state.iter_mut().zip(digest).for_each(|(s, w)| *s = w);
+        Some(state)
+    }
+}
+
+pub type Sha1 = Hasher<Sha1Core>;
+
+#[test]
+fn test_fips180() {
+    let hex = |data: &[u8]| crate::code::encode_hex(&Sha1::digest(data));
+    assert_eq!(hex(b"abc"), "a9993e364706816aba3e25717850c26c9cd0d89d");
+    assert_eq!(hex(b""), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+    assert_eq!(
+        hex(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+        "84983e441c3bd26ebaae4aa1f95129e5e54670f1"
+    );
+    let mut h = Sha1::new();
+    // Odd sized pieces, to cross block boundaries
+    for _ in 0..(1_000_000 / 125) {
+        h.update(&[b'a'; 125]);
+    }
+    let million = crate::code::encode_hex(&h.finalize());
+    assert_eq!(million, "34aa973cd4c4daa4f61eeb2bdbad27316534016f");
+}
@@ -0,0 +1,163 @@
+//! SHA-256 and its truncation SHA-224, FIPS 180-4
+
+use super::{words, Hasher, MerkleDamgard};
+
+use std::convert::TryInto;
+
+pub const BLOCKSIZE: usize = 64;
+
+pub type State = [u32; 8];
+
+pub const IV: State = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c,
+    0x1f83d9ab, 0x5be0cd19,
+];
+
+pub const IV_224: State = [
+    0xc1059ed8, 0x367cd507, 0x3070dd17, 0xf70e5939, 0xffc00b31, 0x68581511,
+    0x64f98fa7, 0xbefa4fa4,
+];
+
+/// Cube roots of the first 64 primes, fractional part
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1,
+    0x923f82a4, 0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3,
+    0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786,
+    0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147,
+    0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13,
+    0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
+    0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a,
+    0x5b9cca4f, 0x682e6ff3, 0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208,
+    0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// One application of the compression function
+pub fn compress(state: &mut State, block: &[u8; BLOCKSIZE]) {
+    let mut w = [0u32; 64];
+    let words = words(block, u32::from_be_bytes);
+    w.iter_mut().zip(words).for_each(|(x, w)| *x = w);
+    for i in 16..64 {
+        let s0 = w[i - 15].rotate_right(7)
+            ^ w[i - 15].rotate_right(18)
+            ^ (w[i - 15] >> 3);
+        let s1 = w[i - 2].rotate_right(17)
+            ^ w[i - 2].rotate_right(19)
+            ^ (w[i - 2] >> 10);
+        w[i] = w[i - 16]
+            .wrapping_add(s0)
+            .wrapping_add(w[i - 7])
+            .wrapping_add(s1);
+    }
+
+    // a, b, c, d, e, f, g, h
+    let mut v = *state;
+    for (k, wi) in K.iter().zip(&w) {
+        let [a, b, c, _, e, f, g, h] = v;
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = h
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(*k)
+            .wrapping_add(*wi);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        v[3] = v[3].wrapping_add(t1);
+        v[7] = t1.wrapping_add(s0.wrapping_add(maj));
+        v.rotate_right(1);
+    }
+    for (s, x) in state.iter_mut().zip(&v) {
+        *s = s.wrapping_add(*x);
+    }
+}
+
+/// The first `N` bytes of the state
+fn output<const N: usize>(state: &State) -> [u8; N] {
+    let full: Vec<u8> = state.iter().flat_map(|s| s.to_be_bytes()).collect();
+    full[..N].try_into().unwrap()
+}
+
+/// Marker for `Hasher`
+#[derive(Clone, Copy)]
+pub struct Sha256Core;
+
+impl MerkleDamgard for Sha256Core {
+    type State = State;
+    type Digest = [u8; 32];
+
+    const BLOCKSIZE: usize = BLOCKSIZE;
+    const IV: State = IV;
+    const LENGTH_BYTES: usize = 8;
+    const BIG_ENDIAN: bool = true;
+
+    fn compress(state: &mut State, block: &[u8]) {
+        compress(state, block.try_into().unwrap());
+    }
+
+    fn output(state: &State) -> [u8; 32] {
+        output(state)
+    }
+
+    fn state_from_digest(digest: &[u8; 32]) -> Option<State> {
+        let mut state = [0; 8];
+        let digest = words(digest, u32::from_be_bytes);
+        state.iter_mut().zip(digest).for_each(|(s, w)| *s = w);
+        Some(state)
+    }
+}
+
+pub type Sha256 = Hasher<Sha256Core>;
+
+/// Marker for `Hasher`
+#[derive(Clone, Copy)]
+pub struct Sha224Core;
+
+impl MerkleDamgard for Sha224Core {
+    type State = State;
+    type Digest = [u8; 28];
+
+    const BLOCKSIZE: usize = BLOCKSIZE;
+    const IV: State = IV_224;
+    const LENGTH_BYTES: usize = 8;
+    const BIG_ENDIAN: bool = true;
+
+    fn compress(state: &mut State, block: &[u8]) {
+        compress(state, block.try_into().unwrap());
+    }
+
+    fn output(state: &State) -> [u8; 28] {
+        output(state)
+    }
+
+    fn state_from_digest(_digest: &[u8; 28]) -> Option<State> {
+        // The last word isn't part of the digest
+        None
+    }
+}
+
+pub type Sha224 = Hasher<Sha224Core>;
+
+#[test]
+fn test_fips180() {
+    use crate::code::encode_hex;
+    let two_blocks =
+        b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+    assert_eq!(
+        encode_hex(&Sha256::digest(b"abc")),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+    assert_eq!(
+        encode_hex(&Sha256::digest(two_blocks)),
+        "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+    );
+    assert_eq!(
+        encode_hex(&Sha224::digest(b"abc")),
+        "23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7"
+    );
+    assert_eq!(
+        encode_hex(&Sha224::digest(two_blocks)),
+        "75388b16512776cc5dba5da1fd890150b0c6455cb4f58b1952522525"
+    );
+}
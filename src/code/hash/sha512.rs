@@ -0,0 +1,294 @@
+//! SHA-512 and its truncations SHA-384 and SHA-512/256, FIPS 180-4.
+//!
+//! Same as SHA-256 but with 64 bit words, 80 rounds and a 128 bit length.
+
+use super::{words, Hasher, MerkleDamgard};
+
+use std::convert::TryInto;
+
+pub const BLOCKSIZE: usize = 128;
+
+pub type State = [u64; 8];
+
+pub const IV: State = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+pub const IV_384: State = [
+    0xcbbb9d5dc1059ed8,
+    0x629a292a367cd507,
+    0x9159015a3070dd17,
+    0x152fecd8f70e5939,
+    0x67332667ffc00b31,
+    0x8eb44a8768581511,
+    0xdb0c2e0d64f98fa7,
+    0x47b5481dbefa4fa4,
+];
+
+pub const IV_512_256: State = [
+    0x22312194fc2bf72c,
+    0x9f555fa3c84c64c2,
+    0x2393b86b6f53b151,
+    0x963877195940eabd,
+    0x96283ee2a88effe3,
+    0xbe5e1e2553863992,
+    0x2b0199fc2c85b8aa,
+    0x0eb72ddc81c52ca2,
+];
+
+/// Cube roots of the first 80 primes, fractional part
+const K: [u64; 80] = [
+    0x428a2f98d728ae22,
+    0x7137449123ef65cd,
+    0xb5c0fbcfec4d3b2f,
+    0xe9b5dba58189dbbc,
+    0x3956c25bf348b538,
+    0x59f111f1b605d019,
+    0x923f82a4af194f9b,
+    0xab1c5ed5da6d8118,
+    0xd807aa98a3030242,
+    0x12835b0145706fbe,
+    0x243185be4ee4b28c,
+    0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f,
+    0x80deb1fe3b1696b1,
+    0x9bdc06a725c71235,
+    0xc19bf174cf692694,
+    0xe49b69c19ef14ad2,
+    0xefbe4786384f25e3,
+    0x0fc19dc68b8cd5b5,
+    0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275,
+    0x4a7484aa6ea6e483,
+    0x5cb0a9dcbd41fbd4,
+    0x76f988da831153b5,
+    0x983e5152ee66dfab,
+    0xa831c66d2db43210,
+    0xb00327c898fb213f,
+    0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2,
+    0xd5a79147930aa725,
+    0x06ca6351e003826f,
+    0x142929670a0e6e70,
+    0x27b70a8546d22ffc,
+    0x2e1b21385c26c926,
+    0x4d2c6dfc5ac42aed,
+    0x53380d139d95b3df,
+    0x650a73548baf63de,
+    0x766a0abb3c77b2a8,
+    0x81c2c92e47edaee6,
+    0x92722c851482353b,
+    0xa2bfe8a14cf10364,
+    0xa81a664bbc423001,
+    0xc24b8b70d0f89791,
+    0xc76c51a30654be30,
+    0xd192e819d6ef5218,
+    0xd69906245565a910,
+    0xf40e35855771202a,
+    0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8,
+    0x1e376c085141ab53,
+    0x2748774cdf8eeb99,
+    0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63,
+    0x4ed8aa4ae3418acb,
+    0x5b9cca4f7763e373,
+    0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc,
+    0x78a5636f43172f60,
+    0x84c87814a1f0ab72,
+    0x8cc702081a6439ec,
+    0x90befffa23631e28,
+    0xa4506cebde82bde9,
+    0xbef9a3f7b2c67915,
+    0xc67178f2e372532b,
+    0xca273eceea26619c,
+    0xd186b8c721c0c207,
+    0xeada7dd6cde0eb1e,
+    0xf57d4f7fee6ed178,
+    0x06f067aa72176fba,
+    0x0a637dc5a2c898a6,
+    0x113f9804bef90dae,
+    0x1b710b35131c471b,
+    0x28db77f523047d84,
+    0x32caab7b40c72493,
+    0x3c9ebe0a15c9bebc,
+    0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6,
+    0x597f299cfc657e2a,
+    0x5fcb6fab3ad6faec,
+    0x6c44198c4a475817,
+];
+
+/// One application of the compression function
+pub fn compress(state: &mut State, block: &[u8; BLOCKSIZE]) {
+    let mut w = [0u64; 80];
+    let words = words(block, u64::from_be_bytes);
+    w.iter_mut().zip(words).for_each(|(x, w)| *x = w);
+    for i in 16..80 {
+        let s0 = w[i - 15].rotate_right(1)
+            ^ w[i - 15].rotate_right(8)
+            ^ (w[i - 15] >> 7);
+        let s1 = w[i - 2].rotate_right(19)
+            ^ w[i - 2].rotate_right(61)
+            ^ (w[i - 2] >> 6);
+        w[i] = w[i - 16]
+            .wrapping_add(s0)
+            .wrapping_add(w[i - 7])
+            .wrapping_add(s1);
+    }
+
+    // a, b, c, d, e, f, g, h
+    let mut v = *state;
+    for (k, wi) in K.iter().zip(&w) {
+        let [a, b, c, _, e, f, g, h] = v;
+        let s1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = h
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(*k)
+            .wrapping_add(*wi);
+        let s0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        v[3] = v[3].wrapping_add(t1);
+        v[7] = t1.wrapping_add(s0.wrapping_add(maj));
+        v.rotate_right(1);
+    }
+    for (s, x) in state.iter_mut().zip(&v) {
+        *s = s.wrapping_add(*x);
+    }
+}
+
+/// The first `N` bytes of the state
+fn output<const N: usize>(state: &State) -> [u8; N] {
+    let full: Vec<u8> = state.iter().flat_map(|s| s.to_be_bytes()).collect();
+    full[..N].try_into().unwrap()
+}
+
+/// Marker for `Hasher`
+#[derive(Clone, Copy)]
+pub struct Sha512Core;
+
+impl MerkleDamgard for Sha512Core {
+    type State = State;
+    type Digest = [u8; 64];
+
+    const BLOCKSIZE: usize = BLOCKSIZE;
+    const IV: State = IV;
+    const LENGTH_BYTES: usize = 16;
+    const BIG_ENDIAN: bool = true;
+
+    fn compress(state: &mut State, block: &[u8]) {
+        compress(state, block.try_into().unwrap());
+    }
+
+    fn output(state: &State) -> [u8; 64] {
+        output(state)
+    }
+
+    fn state_from_digest(digest: &[u8; 64]) -> Option<State> {
+        let mut state = [0; 8];
+        let digest = words(digest, u64::from_be_bytes);
+        state.iter_mut().zip(digest).for_each(|(s, w)| *s = w);
+        Some(state)
+    }
+}
+
+pub type Sha512 = Hasher<Sha512Core>;
+
+/// Marker for `Hasher`
+#[derive(Clone, Copy)]
+pub struct Sha384Core;
+
+impl MerkleDamgard for Sha384Core {
+    type State = State;
+    type Digest = [u8; 48];
+
+    const BLOCKSIZE: usize = BLOCKSIZE;
+    const IV: State = IV_384;
+    const LENGTH_BYTES: usize = 16;
+    const BIG_ENDIAN: bool = true;
+
+    fn compress(state: &mut State, block: &[u8]) {
+        compress(state, block.try_into().unwrap());
+    }
+
+    fn output(state: &State) -> [u8; 48] {
+        output(state)
+    }
+
+    fn state_from_digest(_digest: &[u8; 48]) -> Option<State> {
+        // Two of the eight words aren't part of the digest
+        None
+    }
+}
+
+pub type Sha384 = Hasher<Sha384Core>;
+
+/// Marker for `Hasher`
+#[derive(Clone, Copy)]
+pub struct Sha512_256Core;
+
+impl MerkleDamgard for Sha512_256Core {
+    type State = State;
+    type Digest = [u8; 32];
+
+    const BLOCKSIZE: usize = BLOCKSIZE;
+    const IV: State = IV_512_256;
+    const LENGTH_BYTES: usize = 16;
+    const BIG_ENDIAN: bool = true;
+
+    fn compress(state: &mut State, block: &[u8]) {
+        compress(state, block.try_into().unwrap());
+    }
+
+    fn output(state: &State) -> [u8; 32] {
+        output(state)
+    }
+
+    fn state_from_digest(_digest: &[u8; 32]) -> Option<State> {
+        // Only half the state is in the digest
+        None
+    }
+}
+
+pub type Sha512_256 = Hasher<Sha512_256Core>;
+
+#[test]
+fn test_fips180() {
+    use crate::code::encode_hex;
+    let two_blocks =
+        b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmn\
+        hijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+    assert_eq!(
+        encode_hex(&Sha512::digest(b"abc")),
+        "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+         2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+    );
+    assert_eq!(
+        encode_hex(&Sha512::digest(two_blocks)),
+        "8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018\
+         501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909"
+    );
+    assert_eq!(
+        encode_hex(&Sha384::digest(b"abc")),
+        "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded163\
+         1a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7"
+    );
+    assert_eq!(
+        encode_hex(&Sha512_256::digest(b"abc")),
+        "53048e2681941ef99b2e29b76b4c7dabe4c2d0c634fc6d46e0e2f13107e7af23"
+    );
+    assert_eq!(
+        encode_hex(&Sha512_256::digest(two_blocks)),
+        "3928e184fb8690f840da3988121d31be65cb9d3ef83ee6146feac861e19b563a"
+    );
+}
@@ -0,0 +1,30 @@
+//! `key=value` pairs separated by a single char, like `a=1&b=2`
+
+pub type Pairs = Vec<(String, String)>;
+
+/// None if some pair has no `=`
+pub fn parse(inp: &str, sep: char) -> Option<Pairs> {
+    inp.split(sep)
+        .map(|kv_str| {
+            let (k, v) = kv_str.split_once('=')?;
+            Some((String::from(k), String::from(v)))
+        })
+        .collect()
+}
+
+/// Removes the chars that would let `inp` inject pairs
+pub fn sanitize(inp: &str, sep: char) -> String {
+    inp.chars().filter(|c| *c != sep).filter(|c| *c != '=').collect()
+}
+
+pub fn encode(pairs: &[(String, String)], sep: char) -> String {
+    let strs: Vec<_> = pairs
+        .iter()
+        .map(|(k, v)| format!("{}={}", sanitize(k, sep), sanitize(v, sep)))
+        .collect();
+    strs.join(&sep.to_string())
+}
+
+pub fn get<'a>(pairs: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    pairs.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+}
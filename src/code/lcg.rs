@@ -0,0 +1,159 @@
+//! Linear congruential generators,
+//! state = a * state + c mod 2^modbits
+//! with the output being some of the bits of the new state.
+
+use rayon::prelude::*;
+
+#[derive(Clone, Copy, Debug)]
+pub struct LcgParams {
+    pub a: u64,
+    pub c: u64,
+    pub modbits: u32,
+    /// Output is `outbits` bits of the state, starting at bit `shift`
+    pub shift: u32,
+    pub outbits: u32,
+}
+
+/// glibc `random` with a TYPE_0 state (and `rand` from many old libcs)
+pub const GLIBC_TYPE0: LcgParams = LcgParams {
+    a: 1103515245,
+    c: 12345,
+    modbits: 31,
+    shift: 0,
+    outbits: 31,
+};
+
+/// `java.util.Random::nextInt()`
+pub const JAVA: LcgParams = LcgParams {
+    a: 0x5DEECE66D,
+    c: 0xB,
+    modbits: 48,
+    shift: 16,
+    outbits: 32,
+};
+
+/// MSVC `rand`
+pub const MSVC: LcgParams = LcgParams {
+    a: 214013,
+    c: 2531011,
+    modbits: 32,
+    shift: 16,
+    outbits: 15,
+};
+
+impl LcgParams {
+    fn state_mask(&self) -> u64 {
+        mask(self.modbits)
+    }
+
+    fn step(&self, state: u64) -> u64 {
+        self.a.wrapping_mul(state).wrapping_add(self.c) & self.state_mask()
+    }
+
+    /// Inverse of `step`, a is odd so has an inverse mod 2^modbits
+    fn step_back(&self, state: u64) -> u64 {
+        let a_inv = inverse_mod_pow2(self.a);
+        state.wrapping_sub(self.c).wrapping_mul(a_inv) & self.state_mask()
+    }
+
+    fn output(&self, state: u64) -> u32 {
+        ((state >> self.shift) & mask(self.outbits)) as u32
+    }
+}
+
+fn mask(bits: u32) -> u64 {
+    if bits >= 64 {
+        !0
+    } else {
+        (1 << bits) - 1
+    }
+}
+
+/// Inverse of odd `a` mod 2^64, by Newton iteration.
+/// Every iteration doubles the number of correct low bits.
+fn inverse_mod_pow2(a: u64) -> u64 {
+    assert!(a % 2 == 1);
+    // a * a = 1 mod 8 for odd a, so 3 bits to start with
+    let mut x = a;
+    for _ in 0..5 {
+        x = x.wrapping_mul(2u64.wrapping_sub(a.wrapping_mul(x)));
+    }
+    x
+}
+
+#[derive(Clone, Debug)]
+pub struct Lcg {
+    params: LcgParams,
+    state: u64,
+}
+
+impl Lcg {
+    pub fn new(params: LcgParams, seed: u64) -> Self {
+        Self::from_state(params, seed)
+    }
+
+    /// Seeded like `java.util.Random`, which scrambles the seed
+    pub fn java(seed: u64) -> Self {
+        Self::from_state(JAVA, seed ^ JAVA.a)
+    }
+
+    pub fn from_state(params: LcgParams, state: u64) -> Self {
+        let state = state & params.state_mask();
+        Self { params, state }
+    }
+
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    pub fn get_next(&mut self) -> u32 {
+        self.state = self.params.step(self.state);
+        self.params.output(self.state)
+    }
+}
+
+impl Iterator for Lcg {
+    type Item = u32;
+    fn next(&mut self) -> Option<u32> {
+        Some(self.get_next())
+    }
+}
+
+/// Recovers a generator that outputs `outputs` next, from consecutive
+/// outputs.
+///
+/// The `shift` low bits of the state are never output, so they are brute
+/// forced and every guess is checked against the rest of the outputs. State
+/// bits above the output never affect lower bits (the modulus is a power of
+/// 2), so they are set to 0.
+/// OBS: 2^shift guesses, only practical for a shift up to about 32.
+pub fn crack(params: LcgParams, outputs: &[u32]) -> Option<Lcg> {
+    assert!(!outputs.is_empty());
+    let known = (outputs[0] as u64) << params.shift;
+    (0..(1u64 << params.shift))
+        .into_par_iter()
+        .map(|low| Lcg::from_state(params, known | low))
+        .find_any(|rng| {
+            rng.clone()
+                .zip(&outputs[1..])
+                .all(|(guess, out)| guess == *out)
+        })
+        .map(|rng| {
+            // rng has already output outputs[0]
+            let state = params.step_back(rng.state);
+            Lcg::from_state(params, state)
+        })
+}
+
+#[test]
+fn test_crack() {
+    for (params, count) in &[(GLIBC_TYPE0, 3), (JAVA, 3), (MSVC, 6)] {
+        let mut rng = Lcg::new(*params, 0x1234567);
+        rng.by_ref().take(100).for_each(drop);
+        let outputs: Vec<_> = rng.clone().take(*count).collect();
+        let cracked = crack(*params, &outputs).unwrap();
+        assert!(cracked.take(100).eq(rng.take(100)));
+    }
+    let java_first = Lcg::java(42).get_next();
+    assert!(java_first as i32 == -1170105035);
+}
@@ -1,15 +1,27 @@
-use base64;
 use hex;
 use std::fs;
-use std::time::SystemTime;
 
 
-// == Private imports ==
-mod aes_blackbox;
-
 // == Re-exports ==
 pub mod mt19937;
 pub use mt19937::MT19937;
+pub mod mt19937_solver;
+pub mod gf2;
+pub mod prng_cipher;
+pub mod lcg;
+pub mod xorshift;
+pub mod time;
+pub mod rng;
+pub mod kv;
+pub mod encoding;
+pub mod display;
+pub mod analysis;
+pub mod hash;
+pub mod bigint;
+pub mod dh;
+pub mod protocol;
+pub mod oracles;
+pub mod attacks;
 pub mod english2;
 pub mod english4;
 pub mod english5;
@@ -31,12 +43,11 @@ pub fn encode_hex(bytes: &[u8]) -> String {
 }
 
 pub fn decode_base64(b64: &str) -> Vec<u8> {
-    let b64 = purge_ws(b64);
-    base64::decode(&b64).unwrap()
+    encoding::base64::decode_lenient(b64, encoding::base64::STANDARD).unwrap()
 }
 
 pub fn encode_base64(bytes: &[u8]) -> String {
-    base64::encode(bytes)
+    encoding::base64::encode(bytes, encoding::base64::STANDARD)
 }
 
 pub fn hex_to_base64(hexstr: &str) -> String {
@@ -137,24 +148,21 @@ pub fn pkcs7_unpad(inp: &[u8]) -> &[u8] {
 */
 
 pub fn rnd(len: usize) -> Vec<u8> {
-    (0..len).map(|_| rand::random()).collect()
+    rng::bytes(len)
 }
 
 /// Both inclusive
 pub fn rnd_interval(min: usize, max: usize) -> usize {
-    let x: usize = rand::random();
-    return (x % (max - min + 1)) + min;
+    rng::interval(min, max)
 }
 
+/// OBS: stops at the first difference, so it leaks timing. Secrets like
+/// MACs should use `hash::hmac::constant_time_eq`.
 pub fn cmp_blocks(a: &[u8], b: &[u8]) -> bool {
     assert!(a.len() == b.len());
     a.iter().zip(b.iter()).all(|(x, y)| x == y)
 }
 
-pub fn blackbox_aes() -> &'static aes128::Aes128 {
-    aes_blackbox::blackbox()
-}
-
 // Functional aes ecb encrypt
 pub fn ecb(aes: &aes128::Aes128, plain: &[u8]) -> Vec<u8> {
     let mut buf = Vec::from(plain);
@@ -195,28 +203,3 @@ where
         })
         .min_by_key(|(_, score, _)| *score)
 }
-
-pub struct Clock(u32);
-
-impl Clock {
-    pub fn now() -> Self {
-        let t = SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .expect("What year is it?");
-        Clock(t.as_secs() as u32)
-    }
-
-    pub fn wait(&mut self, seconds: u32) {
-        self.0 += seconds;
-    }
-
-    /// Waits a random number of seconds
-    pub fn waitsome(&mut self) {
-        let t = rnd_interval(0, 9) as u32;
-        self.wait(t);
-    }
-
-    pub fn timestamp(&self) -> u32 {
-        self.0
-    }
-}
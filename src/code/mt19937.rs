@@ -1,18 +1,23 @@
+use rayon::prelude::*;
+use std::ops::RangeInclusive;
+
 const W: usize = 32;
-const N: usize = 624;
-const M: usize = 397;
+pub(crate) const N: usize = 624;
+pub(crate) const M: usize = 397;
 const R: usize = 31;
 
-const U: usize = 11;
-const S: usize = 7;
-const T: usize = 15;
-const L: usize = 18;
+pub(crate) const U: usize = 11;
+pub(crate) const S: usize = 7;
+pub(crate) const T: usize = 15;
+pub(crate) const L: usize = 18;
 
-const A: u32 = 0x9908b0df;
-const D: u32 = 0xffffffff;
-const B: u32 = 0x9d2c5680;
-const C: u32 = 0xefc60000;
+pub(crate) const A: u32 = 0x9908b0df;
+pub(crate) const D: u32 = 0xffffffff;
+pub(crate) const B: u32 = 0x9d2c5680;
+pub(crate) const C: u32 = 0xefc60000;
 const F: u32 = 1812433253;
+/// Inverse of F mod 2^32
+const F_INV: u32 = 2520285293;
 
 const LOWER_MASK: u32 = (1u32 << R) - 1;
 const UPPER_MASK: u32 = LOWER_MASK ^ 0xffffffff;
@@ -56,36 +61,220 @@ impl MT19937 {
             self.twist();
         }
 
-        // "temper"
-        let mut y = self.mt[self.index];
-        //y ^= (y >> U) & D;
-        //y ^= (y << S) & B;
-        //y ^= (y << T) & C;
-        //y ^= y >> L;
-        y = right_and(U, D, y);
-        y = left_and(S, B, y);
-        y = left_and(T, C, y);
-        y = right_and(L, 0xffffffff, y);
-
+        let y = temper(self.mt[self.index]);
         self.index += 1;
         return y;
     }
 
+    /// Steps back one output and returns it, so `get_prev` right after
+    /// `get_next` returns the same number again.
+    /// Can rewind past twists, but not to before the seeded state.
+    pub fn get_prev(&mut self) -> u32 {
+        assert!(self.index <= N);
+        if self.index == 0 {
+            self.untwist();
+        }
+        self.index -= 1;
+        temper(self.mt[self.index])
+    }
+
+    /// The raw state words and the index of the next one to be output
+    pub fn state(&self) -> (&[u32], usize) {
+        (&self.mt, self.index)
+    }
+
+    /// Recovers the state from before the last twist.
+    /// Assumes that state itself came from a twist. If it instead came from
+    /// seeding, the lower bits of the first word will be wrong.
+    pub fn untwist(&mut self) {
+        // Twist did, in order from i = 0:
+        //   mt[i] = mt[i+M] ^ twist_a(upper(mt[i]) | lower(mt[i+1]))
+        // where mt[i+M] and mt[i+1] (mod N) were already twisted if they were
+        // at lower indices. Going backwards from i = N-1, mt[i+M] is then
+        // always what it was when mt[i] was twisted.
+        for i in (0..N).rev() {
+            let x = untwist_a(self.mt[i] ^ self.mt[(i + M) % N]);
+            let next = (i + 1) % N;
+            self.mt[i] = (x & UPPER_MASK) | (self.mt[i] & LOWER_MASK);
+            self.mt[next] = (self.mt[next] & UPPER_MASK) | (x & LOWER_MASK);
+        }
+        // The lower bits of mt[0] never affected the twist. But if this
+        // state was twisted too, mt[N-1] was made from them:
+        let x = untwist_a(self.mt[N - 1] ^ self.mt[M - 1]);
+        self.mt[0] = (self.mt[0] & UPPER_MASK) | (x & LOWER_MASK);
+        self.index = N;
+    }
 
     fn twist(&mut self) {
         for i in 0..N {
             let x =
                 (self.mt[i] & UPPER_MASK) + (self.mt[(i + 1) % N] & LOWER_MASK);
-            let mut x_a = x >> 1;
-            if x % 2 != 0 {
-                x_a ^= A;
-            }
-            self.mt[i] = self.mt[(i + M) % N] ^ x_a;
+            self.mt[i] = self.mt[(i + M) % N] ^ twist_a(x);
         }
         self.index = 0;
     }
 }
 
+pub fn temper(mut y: u32) -> u32 {
+    //y ^= (y >> U) & D;
+    //y ^= (y << S) & B;
+    //y ^= (y << T) & C;
+    //y ^= y >> L;
+    y = right_and(U, D, y);
+    y = left_and(S, B, y);
+    y = left_and(T, C, y);
+    right_and(L, 0xffffffff, y)
+}
+
+/// x_a in `twist`
+fn twist_a(x: u32) -> u32 {
+    let x_a = x >> 1;
+    if x % 2 != 0 {
+        x_a ^ A
+    } else {
+        x_a
+    }
+}
+
+/// Inverse of `twist_a`.
+/// A has its top bit set, so that bit tells if x was odd.
+fn untwist_a(x_a: u32) -> u32 {
+    if x_a >> 31 == 1 {
+        ((x_a ^ A) << 1) | 1
+    } else {
+        x_a << 1
+    }
+}
+
+/// Max number of outputs `first_outputs` can compute.
+/// After that the twist starts reading words it has already twisted.
+pub const FAST_OUTPUTS: usize = N - M;
+
+/// Computes the first `out.len()` outputs of `MT19937::new(seed)`.
+/// Output i only needs the seeded words i, i+1 and i+M, so this skips
+/// building the full state and twisting all of it.
+pub fn first_outputs(seed: u32, out: &mut [u32]) {
+    first_raw_outputs(seed, out);
+    for x in out.iter_mut() {
+        *x = temper(*x);
+    }
+}
+
+/// Like `first_outputs` but without tempering, so the state words right after
+/// the first twist.
+pub fn first_raw_outputs(seed: u32, out: &mut [u32]) {
+    let k = out.len();
+    assert!(k <= FAST_OUTPUTS);
+    // Seeded words 0..=k, the ones that end up in upper/lower of x
+    let mut head = [0u32; FAST_OUTPUTS + 1];
+    let mut w = seed;
+    for i in 0..(k + M) {
+        if i > 0 {
+            w = F.wrapping_mul(w ^ (w >> (W - 2))).wrapping_add(i as u32);
+        }
+        if i <= k {
+            head[i] = w;
+        }
+        if i >= M {
+            // w is seeded word i = j + M
+            let j = i - M;
+            let x = (head[j] & UPPER_MASK) + (head[j + 1] & LOWER_MASK);
+            out[j] = w ^ twist_a(x);
+        }
+    }
+}
+
+/// Number of seeds `first_output_lanes` handles at once
+const LANES: usize = 8;
+
+/// The first output for `LANES` seeds at once.
+/// Seeding is one long chain of dependent multiplications, so interleaving
+/// several seeds keeps the cpu busy and lets the loop be vectorised.
+fn first_output_lanes(seeds: [u32; LANES]) -> [u32; LANES] {
+    let mut w = seeds;
+    let mut w1 = [0u32; LANES];
+    for i in 1..=M {
+        for x in w.iter_mut() {
+            *x = F.wrapping_mul(*x ^ (*x >> (W - 2))).wrapping_add(i as u32);
+        }
+        if i == 1 {
+            w1 = w;
+        }
+    }
+    // w is now seeded word M for every lane
+    let mut out = [0u32; LANES];
+    for (o, ((s, s1), wm)) in
+        out.iter_mut().zip(seeds.iter().zip(&w1).zip(&w))
+    {
+        let x = (s & UPPER_MASK) + (s1 & LOWER_MASK);
+        *o = temper(wm ^ twist_a(x));
+    }
+    out
+}
+
+/// True if `MT19937::new(seed)` starts with `prefix`
+fn has_prefix(seed: u32, prefix: &[u32]) -> bool {
+    if prefix.len() <= FAST_OUTPUTS {
+        let mut out = [0u32; FAST_OUTPUTS];
+        first_outputs(seed, &mut out[..prefix.len()]);
+        out[..prefix.len()] == *prefix
+    } else {
+        MT19937::new(seed).take(prefix.len()).eq(prefix.iter().copied())
+    }
+}
+
+/// Searches `seeds` in parallel for one whose first outputs are `prefix`.
+/// Cheap enough per seed to search all 2^32 seeds.
+pub fn find_seed(seeds: RangeInclusive<u32>, prefix: &[u32]) -> Option<u32> {
+    assert!(!prefix.is_empty());
+    let (lo, hi) = (*seeds.start() as u64, *seeds.end() as u64);
+    if lo > hi {
+        return None;
+    }
+    let chunks = (hi - lo) / LANES as u64 + 1;
+    (0..chunks).into_par_iter().find_map_any(|c| {
+        let base = lo + c * LANES as u64;
+        let mut lanes = [0u32; LANES];
+        for (l, seed) in lanes.iter_mut().enumerate() {
+            // Last chunk might stick out, just repeat the last seed
+            *seed = (base + l as u64).min(hi) as u32;
+        }
+        let outs = first_output_lanes(lanes);
+        lanes
+            .iter()
+            .zip(outs.iter())
+            .filter(|(_, out)| **out == prefix[0])
+            .map(|(seed, _)| *seed)
+            .find(|seed| has_prefix(*seed, prefix))
+    })
+}
+
+/// Recovers the seed from a freshly seeded state, ie before any twist.
+/// Only the words after the first are used, so they must be exact.
+/// None if the state didn't come from seeding.
+pub fn recover_seed(mt: &[u32]) -> Option<u32> {
+    assert!(mt.len() == N);
+    // mt[1] = F * (seed ^ (seed >> 30)) + 1
+    let x = mt[1].wrapping_sub(1).wrapping_mul(F_INV);
+    // Top two bits of x are those of seed, so this gets back seed
+    let seed = x ^ (x >> (W - 2));
+    let check = MT19937::new(seed).mt;
+    if check[1..] == mt[1..] {
+        Some(seed)
+    } else {
+        None
+    }
+}
+
+/// Recovers the seed from the first N outputs after seeding
+pub fn recover_seed_from_outputs(outputs: &[u32]) -> Option<u32> {
+    assert!(outputs.len() == N);
+    let twisted: Vec<_> = outputs.iter().copied().map(untemper).collect();
+    let mut rng = MT19937::from_state(&twisted, N);
+    rng.untwist();
+    recover_seed(&rng.mt)
+}
+
 pub fn untemper(mut y: u32) -> u32 {
     y = invert_right_and(L, 0xffffffff, y);
     y = invert_left_and(T, C, y);
@@ -174,3 +363,20 @@ fn test_default() {
     let result: Vec<_> = MT19937::default().take(TEST_VEC.len()).collect();
     assert!(result == TEST_VEC);
 }
+
+#[test]
+fn test_rewind_and_seed() {
+    let mut rng = MT19937::new(1234);
+    let fwd: Vec<_> = rng.by_ref().take(3 * N + 5).collect();
+    let back: Vec<_> = fwd.iter().map(|_| rng.get_prev()).collect();
+    assert!(back.iter().rev().eq(fwd.iter()));
+    assert!(recover_seed_from_outputs(&fwd[..N]) == Some(1234));
+}
+
+#[test]
+fn test_first_outputs() {
+    let mut out = [0u32; FAST_OUTPUTS];
+    first_outputs(DEFAULT_SEED, &mut out);
+    assert!(MT19937::default().take(FAST_OUTPUTS).eq(out.iter().copied()));
+    assert!(find_seed(5000..=6000, &out[..2]) == Some(DEFAULT_SEED));
+}
@@ -0,0 +1,217 @@
+//! Recovers MT19937 state from partial outputs.
+//!
+//! Both tempering and twisting are linear over GF(2), so every output bit is
+//! an xor of some of the 624 * 32 state bits. Each known output bit gives one
+//! equation, and with enough of them the state can be solved for. This works
+//! for truncated outputs (`getrandbits(8)`, outputs mod 2^k, top bits of
+//! floats) and with gaps, as long as the offsets of the known outputs are
+//! known.
+//!
+//! The recovered state is the state right after a twist, so offset 0 is the
+//! first output of a block of 624.
+
+use crate::code::gf2::{BitRow, LinearSystem};
+use crate::code::mt19937::{A, B, C, D, L, M, N, S, T, U};
+use crate::code::MT19937;
+
+use std::fmt;
+
+const VARS: usize = N * 32;
+
+/// Symbolic 32-bit word: `word[i]` is the set of state bits that xor together
+/// to bit `i` of the word.
+type Word = Vec<BitRow>;
+
+/// Some known bits of the output at `offset`.
+/// Only bits set in `mask` are used from `value`.
+#[derive(Clone, Copy, Debug)]
+pub struct Observation {
+    pub offset: usize,
+    pub value: u32,
+    pub mask: u32,
+}
+
+impl Observation {
+    pub fn full(offset: usize, value: u32) -> Self {
+        Self::masked(offset, value, 0xffffffff)
+    }
+
+    pub fn masked(offset: usize, value: u32, mask: u32) -> Self {
+        Self { offset, value, mask }
+    }
+
+    /// `value` is the top `bits` bits of the output shifted down,
+    /// like python's `getrandbits(bits)`.
+    pub fn top_bits(offset: usize, value: u32, bits: usize) -> Self {
+        assert!(bits > 0 && bits <= 32);
+        let shift = 32 - bits;
+        Self::masked(offset, value << shift, 0xffffffff << shift)
+    }
+
+    /// `value` is the output mod 2^`bits`
+    pub fn low_bits(offset: usize, value: u32, bits: usize) -> Self {
+        assert!(bits > 0 && bits <= 32);
+        Self::masked(offset, value, 0xffffffff >> (32 - bits))
+    }
+}
+
+pub enum SolveError {
+    /// Some observations contradict each other
+    Inconsistent,
+    /// Too few known bits to pin down the state. `guess` agrees with all
+    /// observations, but `free_bits` state bits were just set to 0.
+    Underdetermined {
+        free_bits: usize,
+        guess: Box<MT19937>,
+    },
+}
+
+impl fmt::Debug for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SolveError::Inconsistent => write!(f, "Inconsistent"),
+            SolveError::Underdetermined { free_bits, .. } => {
+                write!(f, "Underdetermined ({} free bits)", free_bits)
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Solver {
+    observations: Vec<Observation>,
+}
+
+impl Solver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn observe(&mut self, obs: Observation) {
+        self.observations.push(obs);
+    }
+
+    /// Returns a generator positioned at offset 0.
+    /// OBS: slow, a dense system of ~20000 equations is eliminated. Run in
+    /// release mode.
+    pub fn solve(mut self) -> Result<MT19937, SolveError> {
+        self.observations.sort_by_key(|o| o.offset);
+
+        let mut sys = LinearSystem::new(VARS);
+        let mut state: Vec<Word> = (0..N).map(word_vars).collect();
+        let mut block = 0;
+        for obs in &self.observations {
+            while obs.offset / N > block {
+                sym_twist(&mut state);
+                block += 1;
+            }
+            let out = sym_temper(&state[obs.offset % N]);
+            for (i, row) in out.into_iter().enumerate() {
+                if (obs.mask >> i) & 1 == 1 {
+                    sys.add(row, (obs.value >> i) & 1 == 1);
+                }
+            }
+        }
+
+        let x = sys.solve().ok_or(SolveError::Inconsistent)?;
+        let mt: Vec<u32> = (0..N)
+            .map(|w| {
+                (0..32)
+                    .filter(|i| x.get(w * 32 + i))
+                    .fold(0u32, |acc, i| acc | (1 << i))
+            })
+            .collect();
+        let rng = MT19937::from_state(&mt, 0);
+        match sys.free_vars() {
+            0 => Ok(rng),
+            free_bits => Err(SolveError::Underdetermined {
+                free_bits,
+                guess: Box::new(rng),
+            }),
+        }
+    }
+}
+
+/// The unknown state word `w`
+fn word_vars(w: usize) -> Word {
+    (0..32).map(|i| BitRow::unit(VARS, w * 32 + i)).collect()
+}
+
+/// Symbolic y ^ ((y >> s) & a)
+fn sym_right_and(s: usize, a: u32, y: &mut Word) {
+    // Bit i reads bit i+s, which is changed later
+    for i in 0..(32 - s) {
+        if (a >> i) & 1 == 1 {
+            let (lo, hi) = y.split_at_mut(i + s);
+            lo[i].xor_with(&hi[0]);
+        }
+    }
+}
+
+/// Symbolic y ^ ((y << s) & a)
+fn sym_left_and(s: usize, a: u32, y: &mut Word) {
+    // Bit i reads bit i-s, which is changed later
+    for i in (s..32).rev() {
+        if (a >> i) & 1 == 1 {
+            let (lo, hi) = y.split_at_mut(i);
+            hi[0].xor_with(&lo[i - s]);
+        }
+    }
+}
+
+/// Same steps as `MT19937::get_next`
+fn sym_temper(w: &Word) -> Word {
+    let mut y = w.clone();
+    sym_right_and(U, D, &mut y);
+    sym_left_and(S, B, &mut y);
+    sym_left_and(T, C, &mut y);
+    sym_right_and(L, 0xffffffff, &mut y);
+    y
+}
+
+/// Same steps as `MT19937::twist`, inplace so later words see the already
+/// twisted ones.
+fn sym_twist(mt: &mut [Word]) {
+    for i in 0..N {
+        let next = (i + 1) % N;
+        // x = upper bit of mt[i] | lower bits of mt[i+1]
+        // x_a = (x >> 1) ^ (A if lowest bit of x)
+        let mut new = mt[(i + M) % N].clone();
+        for j in 0..31 {
+            let src = if j + 1 == 31 { &mt[i][31] } else { &mt[next][j + 1] };
+            new[j].xor_with(src);
+        }
+        for (j, bit) in new.iter_mut().enumerate() {
+            if (A >> j) & 1 == 1 {
+                bit.xor_with(&mt[next][0]);
+            }
+        }
+        mt[i] = new;
+    }
+}
+
+#[test]
+fn test_full_and_missing() {
+    let real: Vec<_> = MT19937::new(1234).take(2 * N).collect();
+
+    let mut solver = Solver::new();
+    for (i, x) in real.iter().take(N).enumerate() {
+        solver.observe(Observation::full(i, *x));
+    }
+    let rng = solver.solve().unwrap();
+    assert!(rng.take(2 * N).eq(real.iter().copied()));
+
+    // Without the first output the lower bits of mt[0] are never seen
+    let mut solver = Solver::new();
+    for (i, x) in real.iter().enumerate().skip(1).take(N) {
+        solver.observe(Observation::full(i, *x));
+    }
+    match solver.solve() {
+        Err(SolveError::Underdetermined { free_bits, guess }) => {
+            assert!(free_bits == 31);
+            let guess = guess.skip(1).take(2 * N - 1);
+            assert!(guess.eq(real.iter().skip(1).copied()));
+        }
+        _ => panic!("Should be underdetermined"),
+    }
+}
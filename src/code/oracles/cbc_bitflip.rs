@@ -0,0 +1,66 @@
+use super::QueryCounter;
+use crate::code;
+use code::aes128::{Aes128, BLOCKSIZE};
+
+pub const PREFIX: &str = "comment1=cooking%20MCs;userdata=";
+pub const SUFFIX: &str = ";comment2=%20like%20a%20pound%20of%20bacon";
+
+/// Encrypts userdata in a `;` separated cookie, from challenge 16
+pub struct CbcBitflipOracle {
+    aes: Aes128,
+    queries: QueryCounter,
+}
+
+impl CbcBitflipOracle {
+    pub fn new() -> Self {
+        Self::with_key(&code::rnd(BLOCKSIZE))
+    }
+
+    pub fn with_key(key: &[u8]) -> Self {
+        Self {
+            aes: Aes128::new(key),
+            queries: QueryCounter::default(),
+        }
+    }
+
+    /// Prepends `PREFIX` and appends `SUFFIX` to the sanitized `userdata`,
+    /// then encrypts in CBC with random iv.
+    /// Returns (iv,cipher)
+    pub fn encrypt(&self, userdata: &str) -> (Vec<u8>, Vec<u8>) {
+        self.queries.tick();
+        let sanitized = code::kv::sanitize(userdata, ';');
+        let mut buf: Vec<_> = PREFIX
+            .bytes()
+            .chain(sanitized.bytes())
+            .chain(SUFFIX.bytes())
+            .collect();
+        let iv = code::rnd(BLOCKSIZE);
+        self.aes.cbc_encr(&iv, &mut buf);
+        (iv, buf)
+    }
+
+    /// Doesn't parse or remove padding
+    pub fn decrypt(&self, iv: &[u8], cipher: &[u8]) -> Vec<u8> {
+        self.queries.tick();
+        let mut buf = Vec::from(cipher);
+        self.aes.cbc_decr(iv, &mut buf);
+        buf
+    }
+
+    /// True if the decrypted cookie contains `;admin=true;`
+    pub fn is_admin(&self, iv: &[u8], cipher: &[u8]) -> bool {
+        let plain = self.decrypt(iv, cipher);
+        let needle = b";admin=true;";
+        plain.windows(needle.len()).any(|w| w == needle)
+    }
+
+    pub fn queries(&self) -> usize {
+        self.queries.count()
+    }
+}
+
+impl Default for CbcBitflipOracle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
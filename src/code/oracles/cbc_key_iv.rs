@@ -0,0 +1,60 @@
+use super::QueryCounter;
+use crate::code;
+use code::aes128::{Aes128, BLOCKSIZE};
+
+/// The complaint about a message that isn't ascii, quoting the plaintext
+#[derive(Clone, Debug)]
+pub struct HighAscii {
+    pub plain: Vec<u8>,
+}
+
+/// CBC with the key also used as iv, from challenge 27
+pub struct CbcKeyIvOracle {
+    key: Vec<u8>,
+    aes: Aes128,
+    queries: QueryCounter,
+}
+
+impl CbcKeyIvOracle {
+    pub fn new() -> Self {
+        Self::with_key(&code::rnd(BLOCKSIZE))
+    }
+
+    pub fn with_key(key: &[u8]) -> Self {
+        Self {
+            key: Vec::from(key),
+            aes: Aes128::new(key),
+            queries: QueryCounter::default(),
+        }
+    }
+
+    pub fn encrypt(&self, plain: &[u8]) -> Vec<u8> {
+        self.queries.tick();
+        let mut buf = Vec::from(plain);
+        self.aes.cbc_encr(&self.key, &mut buf);
+        buf
+    }
+
+    /// Errors with the whole plaintext if any byte of it is over 127.
+    /// Doesn't check or remove padding.
+    pub fn decrypt(&self, cipher: &[u8]) -> Result<(), HighAscii> {
+        self.queries.tick();
+        let mut buf = Vec::from(cipher);
+        self.aes.cbc_decr(&self.key, &mut buf);
+        if buf.is_ascii() {
+            Ok(())
+        } else {
+            Err(HighAscii { plain: buf })
+        }
+    }
+
+    pub fn queries(&self) -> usize {
+        self.queries.count()
+    }
+}
+
+impl Default for CbcKeyIvOracle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,51 @@
+use super::QueryCounter;
+use crate::code;
+use code::aes128::{Aes128, BLOCKSIZE};
+
+/// Encrypts secrets in CBC and tells if a ciphertext decrypts to valid
+/// padding, from challenge 17.
+pub struct CbcPaddingOracle {
+    aes: Aes128,
+    secrets: Vec<Vec<u8>>,
+    queries: QueryCounter,
+}
+
+impl CbcPaddingOracle {
+    pub fn new(secrets: Vec<Vec<u8>>) -> Self {
+        Self::with_key(&code::rnd(BLOCKSIZE), secrets)
+    }
+
+    pub fn with_key(key: &[u8], secrets: Vec<Vec<u8>>) -> Self {
+        assert!(!secrets.is_empty());
+        Self {
+            aes: Aes128::new(key),
+            secrets,
+            queries: QueryCounter::default(),
+        }
+    }
+
+    /// Encrypts a random one of the secrets with a random iv.
+    /// Returns (iv,cipher)
+    pub fn encrypt_secret(&self) -> (Vec<u8>, Vec<u8>) {
+        let i = code::rnd_interval(0, self.secrets.len() - 1);
+        let iv = code::rnd(BLOCKSIZE);
+        let mut buf = self.secrets[i].clone();
+        self.aes.cbc_encr(&iv, &mut buf);
+        (iv, buf)
+    }
+
+    /// False also if `cipher` isn't a whole number of blocks
+    pub fn check_padding(&self, iv: &[u8], cipher: &[u8]) -> bool {
+        self.queries.tick();
+        if cipher.is_empty() || !cipher.len().is_multiple_of(BLOCKSIZE) {
+            return false;
+        }
+        let mut buf = Vec::from(cipher);
+        self.aes.cbc_decr(iv, &mut buf);
+        code::pkcs7_validate(&buf).is_some()
+    }
+
+    pub fn queries(&self) -> usize {
+        self.queries.count()
+    }
+}
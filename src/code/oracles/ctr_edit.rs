@@ -0,0 +1,52 @@
+use super::QueryCounter;
+use crate::code;
+use code::aes128::{Aes128, BLOCKSIZE};
+
+/// Disk encryption with CTR that allows seeking writes, from challenge 25.
+/// Holds an encrypted secret, and re-encrypts any ciphertext with part of
+/// it replaced.
+pub struct CtrEditOracle {
+    aes: Aes128,
+    nonce: u64,
+    secret: Vec<u8>,
+    queries: QueryCounter,
+}
+
+impl CtrEditOracle {
+    pub fn new(plain: &[u8]) -> Self {
+        let nonce = code::rng::random();
+        Self::with_key(&code::rnd(BLOCKSIZE), nonce, plain)
+    }
+
+    pub fn with_key(key: &[u8], nonce: u64, plain: &[u8]) -> Self {
+        let aes = Aes128::new(key);
+        let mut secret = Vec::from(plain);
+        aes.ctr_inplace(nonce, &mut secret);
+        Self {
+            aes,
+            nonce,
+            secret,
+            queries: QueryCounter::default(),
+        }
+    }
+
+    /// The encrypted secret
+    pub fn cipher(&self) -> Vec<u8> {
+        self.secret.clone()
+    }
+
+    /// `cipher` with the plaintext from `offset` on replaced by `newtext`
+    pub fn edit(
+        &self,
+        cipher: &[u8],
+        offset: usize,
+        newtext: &[u8],
+    ) -> Vec<u8> {
+        self.queries.tick();
+        self.aes.ctr_edit(self.nonce, cipher, offset, newtext)
+    }
+
+    pub fn queries(&self) -> usize {
+        self.queries.count()
+    }
+}
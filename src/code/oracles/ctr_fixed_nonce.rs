@@ -0,0 +1,42 @@
+use super::QueryCounter;
+use crate::code;
+use code::aes128::{Aes128, BLOCKSIZE};
+
+/// CTR with the same key and nonce for every message, from challenges 19 and
+/// 20
+pub struct CtrFixedNonceOracle {
+    aes: Aes128,
+    nonce: u64,
+    queries: QueryCounter,
+}
+
+impl CtrFixedNonceOracle {
+    pub fn new() -> Self {
+        Self::with_key(&code::rnd(BLOCKSIZE), 0)
+    }
+
+    pub fn with_key(key: &[u8], nonce: u64) -> Self {
+        Self {
+            aes: Aes128::new(key),
+            nonce,
+            queries: QueryCounter::default(),
+        }
+    }
+
+    pub fn encrypt(&self, plain: &[u8]) -> Vec<u8> {
+        self.queries.tick();
+        let mut buf = Vec::from(plain);
+        self.aes.ctr_inplace(self.nonce, &mut buf);
+        buf
+    }
+
+    pub fn queries(&self) -> usize {
+        self.queries.count()
+    }
+}
+
+impl Default for CtrFixedNonceOracle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
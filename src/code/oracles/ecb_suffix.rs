@@ -0,0 +1,51 @@
+use super::QueryCounter;
+use crate::code;
+use code::aes128::{Aes128, BLOCKSIZE};
+
+/// AES-128-ECB(attacker-controlled || secret, key), from challenge 12.
+/// Optionally with a random prefix, from challenge 14.
+pub struct EcbSuffixOracle {
+    aes: Aes128,
+    secret: Vec<u8>,
+    max_prefix: Option<usize>,
+    queries: QueryCounter,
+}
+
+impl EcbSuffixOracle {
+    pub fn new(secret: &[u8]) -> Self {
+        Self::with_key(&code::rnd(BLOCKSIZE), secret)
+    }
+
+    pub fn with_key(key: &[u8], secret: &[u8]) -> Self {
+        Self {
+            aes: Aes128::new(key),
+            secret: Vec::from(secret),
+            max_prefix: None,
+            queries: QueryCounter::default(),
+        }
+    }
+
+    /// Every query also gets between 0 and `max_prefix` (inclusive) random
+    /// bytes prepended, a new number each time
+    pub fn with_random_prefix(mut self, max_prefix: usize) -> Self {
+        self.max_prefix = Some(max_prefix);
+        self
+    }
+
+    /// AES-128-ECB(random-prefix || attacker-controlled || secret, key)
+    pub fn query(&self, attacker: &[u8]) -> Vec<u8> {
+        self.queries.tick();
+        let mut buf = match self.max_prefix {
+            Some(max) => code::rnd(code::rnd_interval(0, max)),
+            None => Vec::new(),
+        };
+        buf.extend(attacker);
+        buf.extend(&self.secret);
+        self.aes.ecb_encr(&mut buf);
+        buf
+    }
+
+    pub fn queries(&self) -> usize {
+        self.queries.count()
+    }
+}
@@ -0,0 +1,92 @@
+use super::cbc_bitflip::{PREFIX, SUFFIX};
+use super::QueryCounter;
+use crate::code;
+use code::encoding::percent;
+use code::hash::{hmac, sha1, Sha1};
+use code::kv;
+
+/// `;` separated cookies authenticated with SHA1(key || message), from
+/// challenges 28 and 29.
+/// OBS: a secret-prefix MAC can be length extended, use `hash::Hmac`.
+pub struct MacCookieOracle {
+    key: Vec<u8>,
+    queries: QueryCounter,
+}
+
+impl MacCookieOracle {
+    /// A random key of random length, up to 32 bytes
+    pub fn new() -> Self {
+        Self::with_key(&code::rnd(code::rnd_interval(1, 32)))
+    }
+
+    pub fn with_key(key: &[u8]) -> Self {
+        Self {
+            key: Vec::from(key),
+            queries: QueryCounter::default(),
+        }
+    }
+
+    pub fn mac(&self, msg: &[u8]) -> [u8; sha1::OUTPUT] {
+        Sha1::digest(&[&self.key, msg].concat())
+    }
+
+    /// `PREFIX`, percent-encoded `userdata` and `SUFFIX`, like the CBC
+    /// cookies. Returns (cookie, mac)
+    pub fn issue(&self, userdata: &str) -> (Vec<u8>, [u8; sha1::OUTPUT]) {
+        self.queries.tick();
+        let quoted = percent::encode(userdata.as_bytes(), b";=");
+        let cookie = format!("{}{}{}", PREFIX, quoted, SUFFIX).into_bytes();
+        let mac = self.mac(&cookie);
+        (cookie, mac)
+    }
+
+    /// The pairs of `cookie` if `mac` is right. Bytes that aren't utf8,
+    /// like glue padding, become U+FFFD.
+    pub fn verify(&self, cookie: &[u8], mac: &[u8]) -> Option<kv::Pairs> {
+        self.queries.tick();
+        if !hmac::constant_time_eq(&self.mac(cookie), mac) {
+            return None;
+        }
+        kv::parse(&String::from_utf8_lossy(cookie), ';')
+    }
+
+    /// True if `cookie` verifies and has `admin=true`
+    pub fn is_admin(&self, cookie: &[u8], mac: &[u8]) -> bool {
+        let pairs = self.verify(cookie, mac);
+        pairs.is_some_and(|p| kv::get(&p, "admin") == Some("true"))
+    }
+
+    pub fn queries(&self) -> usize {
+        self.queries.count()
+    }
+}
+
+impl Default for MacCookieOracle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[test]
+fn test_forge_admin() {
+    use code::hash::{length_extension, sha1::Sha1Core};
+
+    let oracle = MacCookieOracle::with_key(b"YELLOW SUBMARINE");
+    let (cookie, mac) = oracle.issue(";admin=true");
+    assert!(!oracle.is_admin(&cookie, &mac));
+    let mut tampered = cookie.clone();
+    tampered.extend(b";admin=true");
+    assert!(oracle.verify(&tampered, &mac).is_none());
+
+    let verify = |m: &[u8], t: &[u8]| oracle.verify(m, t).is_some();
+    let forged = length_extension::<Sha1Core, _>(
+        &cookie,
+        &mac,
+        b";admin=true",
+        64,
+        verify,
+    )
+    .unwrap();
+    assert_eq!(forged.keylen, 16);
+    assert!(oracle.is_admin(&forged.message, &forged.mac));
+}
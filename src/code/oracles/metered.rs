@@ -0,0 +1,119 @@
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The query that would have gone over the budget
+#[derive(Clone, Copy, Debug)]
+pub struct BudgetExceeded {
+    pub budget: usize,
+}
+
+type Query<'a, T> = Box<dyn Fn(&[u8]) -> T + Send + Sync + 'a>;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Stats {
+    pub queries: usize,
+    /// Total length of all inputs
+    pub bytes: usize,
+    /// From creation until the last answer came back
+    pub time: Duration,
+}
+
+/// Wraps any oracle, as a function from input bytes to answer, and keeps
+/// track of what the attack costs.
+/// Oracles taking more than one argument can be wrapped with a closure that
+/// splits the input, like
+/// `|buf| oracle.check_padding(&buf[..16], &buf[16..])`.
+/// Can be shared between threads.
+pub struct Metered<'a, T> {
+    oracle: Query<'a, T>,
+    budget: Option<usize>,
+    queries: AtomicUsize,
+    bytes: AtomicUsize,
+    created: Instant,
+    last_ns: AtomicU64,
+    log: Option<Mutex<Vec<Vec<u8>>>>,
+}
+
+impl<'a, T> Metered<'a, T> {
+    pub fn new<F>(oracle: F) -> Self
+    where
+        F: Fn(&[u8]) -> T + Send + Sync + 'a,
+    {
+        Self {
+            oracle: Box::new(oracle),
+            budget: None,
+            queries: AtomicUsize::new(0),
+            bytes: AtomicUsize::new(0),
+            created: Instant::now(),
+            last_ns: AtomicU64::new(0),
+            log: None,
+        }
+    }
+
+    /// Refuses to ask the oracle more than `budget` times
+    pub fn with_budget(mut self, budget: usize) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Keeps every input, in the order they were asked
+    pub fn with_log(mut self) -> Self {
+        self.log = Some(Mutex::new(Vec::new()));
+        self
+    }
+
+    pub fn query(&self, input: &[u8]) -> Result<T, BudgetExceeded> {
+        let n = self.queries.fetch_add(1, Ordering::SeqCst);
+        if let Some(budget) = self.budget {
+            if n >= budget {
+                self.queries.fetch_sub(1, Ordering::SeqCst);
+                return Err(BudgetExceeded { budget });
+            }
+        }
+        self.bytes.fetch_add(input.len(), Ordering::Relaxed);
+        if let Some(log) = &self.log {
+            log.lock().unwrap().push(Vec::from(input));
+        }
+        let answer = (self.oracle)(input);
+        let ns = self.created.elapsed().as_nanos() as u64;
+        self.last_ns.fetch_max(ns, Ordering::Relaxed);
+        Ok(answer)
+    }
+
+    pub fn stats(&self) -> Stats {
+        Stats {
+            queries: self.queries.load(Ordering::SeqCst),
+            bytes: self.bytes.load(Ordering::Relaxed),
+            time: Duration::from_nanos(self.last_ns.load(Ordering::Relaxed)),
+        }
+    }
+
+    /// All inputs so far. Empty unless made `with_log`
+    pub fn log(&self) -> Vec<Vec<u8>> {
+        match &self.log {
+            Some(log) => log.lock().unwrap().clone(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Asks `oracle` everything in the log again, eg to check another
+    /// attack variant against the same queries
+    pub fn replay<U, F>(&self, oracle: F) -> Vec<U>
+    where
+        F: Fn(&[u8]) -> U,
+    {
+        self.log().iter().map(|input| oracle(input)).collect()
+    }
+}
+
+#[test]
+fn test_budget_and_log() {
+    let metered = Metered::new(|x: &[u8]| x.len()).with_budget(2).with_log();
+    assert_eq!(metered.query(b"abc").unwrap(), 3);
+    assert_eq!(metered.query(b"de").unwrap(), 2);
+    assert_eq!(metered.query(b"f").unwrap_err().budget, 2);
+    let stats = metered.stats();
+    assert_eq!((stats.queries, stats.bytes), (2, 5));
+    assert_eq!(metered.replay(|x| x[0]), vec![b'a', b'd']);
+}
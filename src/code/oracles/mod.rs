@@ -0,0 +1,66 @@
+//! The targets of the attacks.
+//!
+//! Every oracle owns its own key and secrets, so any number of independent
+//! ones can exist at once. They also count how many times they are queried.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub mod cbc_bitflip;
+mod cbc_key_iv;
+mod cbc_padding;
+mod ctr_edit;
+mod ctr_fixed_nonce;
+mod ecb_suffix;
+mod mac_cookie;
+mod metered;
+mod profile;
+mod timing;
+
+pub use cbc_bitflip::CbcBitflipOracle;
+pub use cbc_key_iv::{CbcKeyIvOracle, HighAscii};
+pub use cbc_padding::CbcPaddingOracle;
+pub use ctr_edit::CtrEditOracle;
+pub use ctr_fixed_nonce::CtrFixedNonceOracle;
+pub use ecb_suffix::EcbSuffixOracle;
+pub use mac_cookie::MacCookieOracle;
+pub use metered::{BudgetExceeded, Metered, Stats};
+pub use profile::ProfileOracle;
+pub use timing::{insecure_compare, TimingOracle, TimingServer};
+
+/// Number of queries so far, shared between threads
+#[derive(Default)]
+pub struct QueryCounter(AtomicUsize);
+
+impl QueryCounter {
+    pub fn tick(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn count(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[test]
+fn test_independent_keys() {
+    let a = CtrFixedNonceOracle::new();
+    let b = CtrFixedNonceOracle::new();
+    let zeros = [0; 32];
+    assert_ne!(a.encrypt(&zeros), b.encrypt(&zeros));
+    assert_eq!(a.encrypt(&zeros), a.encrypt(&zeros));
+    assert_eq!((a.queries(), b.queries()), (3, 1));
+}
+
+#[test]
+fn test_partial_blocks() {
+    let padding = CbcPaddingOracle::new(vec![b"secret".to_vec()]);
+    let profile = ProfileOracle::new();
+    let iv = [0; 16];
+    for len in &[0, 1, 15, 17, 31] {
+        let cipher = vec![0; *len];
+        assert!(!padding.check_padding(&iv, &cipher));
+        assert_eq!(profile.decrypt_profile(&cipher), None);
+    }
+    let cipher = profile.encrypt_profile(b"foo@bar.com");
+    assert!(profile.decrypt_profile(&cipher).is_some());
+}
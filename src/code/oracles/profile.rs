@@ -0,0 +1,64 @@
+use super::QueryCounter;
+use crate::code;
+use code::aes128::{Aes128, BLOCKSIZE};
+use code::kv;
+
+/// `email=...&uid=10&role=user`, the email sanitized
+pub fn profile_for(email: &str) -> String {
+    // Order is important. Using same as in challenge description
+    let pairs = vec![
+        (String::from("email"), String::from(email)),
+        (String::from("uid"), String::from("10")),
+        (String::from("role"), String::from("user")),
+    ];
+    kv::encode(&pairs, '&')
+}
+
+/// ECB encrypted user profiles, from challenge 13
+pub struct ProfileOracle {
+    aes: Aes128,
+    queries: QueryCounter,
+}
+
+impl ProfileOracle {
+    pub fn new() -> Self {
+        Self::with_key(&code::rnd(BLOCKSIZE))
+    }
+
+    pub fn with_key(key: &[u8]) -> Self {
+        Self {
+            aes: Aes128::new(key),
+            queries: QueryCounter::default(),
+        }
+    }
+
+    /// Panics if email isn't utf8
+    pub fn encrypt_profile(&self, email: &[u8]) -> Vec<u8> {
+        self.queries.tick();
+        let email = std::str::from_utf8(email).expect("Invalid email");
+        code::ecb(&self.aes, profile_for(email).as_bytes())
+    }
+
+    /// None if not whole blocks, bad padding or not a profile
+    pub fn decrypt_profile(&self, cipher: &[u8]) -> Option<kv::Pairs> {
+        self.queries.tick();
+        if cipher.is_empty() || !cipher.len().is_multiple_of(BLOCKSIZE) {
+            return None;
+        }
+        let mut buf = Vec::from(cipher);
+        self.aes.ecb_decr(&mut buf);
+        let plain = code::pkcs7_validate(&buf)?;
+        let plain = std::str::from_utf8(plain).ok()?;
+        kv::parse(plain, '&')
+    }
+
+    pub fn queries(&self) -> usize {
+        self.queries.count()
+    }
+}
+
+impl Default for ProfileOracle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,205 @@
+use super::QueryCounter;
+use crate::code;
+use code::encoding::{hex, percent};
+use code::hash::{sha1, Hmac};
+use code::kv;
+use code::time::{Jitter, TimeSource};
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+/// Compares byte by byte and gives up at the first difference, after
+/// sleeping `delay_us` for every byte that matched.
+/// OBS: the time taken tells how long the matching prefix is.
+pub fn insecure_compare(
+    a: &[u8],
+    b: &[u8],
+    clock: &dyn TimeSource,
+    delay_us: u64,
+) -> bool {
+    for (x, y) in a.iter().zip(b) {
+        if x != y {
+            return false;
+        }
+        clock.sleep_us(delay_us);
+    }
+    a.len() == b.len()
+}
+
+/// A web app checking HMAC-SHA1 signatures of file names with
+/// `insecure_compare`, from challenges 31 and 32.
+/// With a `SimClock` it runs instantly and the leak is exact, unless
+/// jitter is added.
+pub struct TimingOracle<C: TimeSource> {
+    key: Vec<u8>,
+    clock: C,
+    delay_us: u64,
+    /// Random wait up to some µs per query
+    jitter: Option<(Mutex<Jitter>, u64)>,
+    queries: QueryCounter,
+}
+
+impl<C: TimeSource> TimingOracle<C> {
+    pub fn new(clock: C, delay_us: u64) -> Self {
+        Self::with_key(&code::rnd(16), clock, delay_us)
+    }
+
+    pub fn with_key(key: &[u8], clock: C, delay_us: u64) -> Self {
+        Self {
+            key: Vec::from(key),
+            clock,
+            delay_us,
+            jitter: None,
+            queries: QueryCounter::default(),
+        }
+    }
+
+    /// Waits another random 0 to `max_us` µs on every query, as noise
+    pub fn with_jitter(mut self, max_us: u64, seed: u64) -> Self {
+        self.jitter = Some((Mutex::new(Jitter::new(seed)), max_us));
+        self
+    }
+
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Whether `signature` is right for `file`, a 200 rather than a 500
+    pub fn check(&self, file: &[u8], signature: &[u8]) -> bool {
+        self.queries.tick();
+        if let Some((jitter, max_us)) = &self.jitter {
+            jitter.lock().unwrap().wait_us(&self.clock, 0, *max_us);
+        }
+        let mac = self.expected(file);
+        insecure_compare(&mac, signature, &self.clock, self.delay_us)
+    }
+
+    /// The right signature, to check an attack with
+    pub fn expected(&self, file: &[u8]) -> [u8; sha1::OUTPUT] {
+        Hmac::<sha1::Sha1Core>::mac(&self.key, file)
+    }
+
+    pub fn queries(&self) -> usize {
+        self.queries.count()
+    }
+}
+
+/// A `TimingOracle` on a local port, answering
+/// `GET /test?file=foo&signature=46b4ec586117154dacd49d664e5d63fdc88efb51`
+/// with 200 or 500. Requests are handled one at a time. Stops when dropped.
+pub struct TimingServer {
+    addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl TimingServer {
+    pub fn spawn<C>(oracle: Arc<TimingOracle<C>>) -> io::Result<Self>
+    where
+        C: TimeSource + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        let handle = thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stopped.load(Ordering::SeqCst) {
+                    break;
+                }
+                if let Ok(stream) = stream {
+                    // A client hanging up is its own problem
+                    let _ = respond(&oracle, stream);
+                }
+            }
+        });
+        Ok(Self {
+            addr,
+            stop,
+            handle: Some(handle),
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+impl Drop for TimingServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        // Wakes up the accept
+        let _ = TcpStream::connect(self.addr);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// (file, signature) from a request line
+fn parse_request(line: &str) -> Option<(Vec<u8>, Vec<u8>)> {
+    let mut words = line.split_whitespace();
+    if words.next()? != "GET" {
+        return None;
+    }
+    let query = words.next()?.strip_prefix("/test?")?;
+    let pairs = kv::parse(query, '&')?;
+    let file = percent::decode(kv::get(&pairs, "file")?).ok()?;
+    let signature = hex::decode(kv::get(&pairs, "signature")?).ok()?;
+    Some((file, signature))
+}
+
+fn respond<C: TimeSource>(
+    oracle: &TimingOracle<C>,
+    mut stream: TcpStream,
+) -> io::Result<()> {
+    let mut reader = BufReader::new(&stream);
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+    // Skips the headers, so closing doesn't reset the connection
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && header.trim() != "" {
+        header.clear();
+    }
+    let status = match parse_request(&request) {
+        Some((file, signature)) if oracle.check(&file, &signature) => "200 OK",
+        Some(_) => "500 Internal Server Error",
+        None => "400 Bad Request",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        status
+    )
+}
+
+#[test]
+fn test_timing_leak() {
+    use code::time::SimClock;
+    use std::io::Read;
+
+    let oracle = TimingOracle::new(SimClock::at_ms(0), 50);
+    let good = oracle.expected(b"foo");
+    let mut bad = good;
+    bad[3] ^= 1;
+    let clock = oracle.clock().clone();
+    assert!(oracle.check(b"foo", &good));
+    assert_eq!(clock.now_us(), 20 * 50);
+    assert!(!oracle.check(b"foo", &bad));
+    assert_eq!(clock.now_us(), 23 * 50);
+
+    let server = TimingServer::spawn(Arc::new(oracle)).unwrap();
+    let get = |sig: &[u8]| {
+        let mut stream = TcpStream::connect(server.addr()).unwrap();
+        let path = format!("/test?file=foo&signature={}", hex::encode(sig));
+        write!(stream, "GET {} HTTP/1.1\r\nHost: x\r\n\r\n", path).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    };
+    assert!(get(&good).starts_with("HTTP/1.1 200"));
+    assert!(get(&bad).starts_with("HTTP/1.1 500"));
+}
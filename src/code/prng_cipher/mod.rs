@@ -0,0 +1,83 @@
+//! Stream ciphers that use the output of a PRNG, seeded with the key, as
+//! keystream.
+
+use crate::code::MT19937;
+
+use std::marker::PhantomData;
+
+pub mod recovery;
+
+/// Generators that can be seeded with a key
+pub trait SeedableGen: Iterator<Item = u32> {
+    fn from_seed(seed: u32) -> Self;
+}
+
+impl SeedableGen for MT19937 {
+    fn from_seed(seed: u32) -> Self {
+        MT19937::new(seed)
+    }
+}
+
+/// How a generator word is split into four keystream bytes
+pub trait WordLayout {
+    /// Bit offset in the word of keystream byte `i`
+    fn byte_shift(i: usize) -> u32;
+
+    fn bytes(w: u32) -> [u8; 4] {
+        let mut out = [0u8; 4];
+        for (i, b) in out.iter_mut().enumerate() {
+            *b = (w >> Self::byte_shift(i)) as u8;
+        }
+        out
+    }
+}
+
+/// Least significant byte first
+pub struct LittleEndian;
+
+impl WordLayout for LittleEndian {
+    fn byte_shift(i: usize) -> u32 {
+        8 * i as u32
+    }
+}
+
+/// Most significant byte first
+pub struct BigEndian;
+
+impl WordLayout for BigEndian {
+    fn byte_shift(i: usize) -> u32 {
+        8 * (3 - i as u32)
+    }
+}
+
+pub struct PrngStreamCipher<R, L = LittleEndian> {
+    seed: u32,
+    _gen: PhantomData<(R, L)>,
+}
+
+impl<R: SeedableGen, L: WordLayout> PrngStreamCipher<R, L> {
+    pub fn new(seed: u32) -> Self {
+        Self {
+            seed,
+            _gen: PhantomData,
+        }
+    }
+
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    pub fn keystream(&self) -> impl Iterator<Item = u8> {
+        R::from_seed(self.seed).flat_map(|w| {
+            let bytes = L::bytes(w);
+            (0..4).map(move |i| bytes[i])
+        })
+    }
+
+    /// Inplace encr/decr
+    pub fn apply(&self, buf: &mut [u8]) {
+        buf.iter_mut()
+            .zip(self.keystream())
+            .for_each(|(x, k)| *x ^= k)
+    }
+}
@@ -0,0 +1,137 @@
+//! Key recovery for `PrngStreamCipher` from known plaintext.
+//!
+//! Known plaintext at known positions gives keystream bytes at the same
+//! positions. The key is then searched for among the seeds, and for MT19937
+//! with 32-bit seeds this is done without running the full generator.
+
+use super::{PrngStreamCipher, SeedableGen, WordLayout};
+use crate::code::mt19937::{self, FAST_OUTPUTS};
+
+use rayon::prelude::*;
+use std::ops::RangeInclusive;
+
+/// Keystream byte at a position in the keystream
+pub type KnownByte = (usize, u8);
+
+/// `plain` is known to be at `offset` in the plaintext of `cipher`
+pub fn known_keystream(
+    cipher: &[u8],
+    offset: usize,
+    plain: &[u8],
+) -> Vec<KnownByte> {
+    assert!(offset + plain.len() <= cipher.len());
+    plain
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (offset + i, p ^ cipher[offset + i]))
+        .collect()
+}
+
+/// Tries all of `seeds` with the full generator
+pub fn recover_seed<R, L>(
+    known: &[KnownByte],
+    seeds: RangeInclusive<u32>,
+) -> Option<u32>
+where
+    R: SeedableGen,
+    L: WordLayout,
+{
+    let len = known.iter().map(|(pos, _)| pos + 1).max()?;
+    seeds.into_par_iter().find_any(|seed| {
+        let keystream: Vec<u8> = PrngStreamCipher::<R, L>::new(*seed)
+            .keystream()
+            .take(len)
+            .collect();
+        known.iter().all(|(pos, k)| keystream[*pos] == *k)
+    })
+}
+
+/// What is known of output word `index`
+enum WordCheck {
+    /// Entire word known, so it's untempered once here instead of tempering
+    /// the guess for every seed
+    Raw { index: usize, raw: u32 },
+    /// Only some bytes known
+    Masked { index: usize, value: u32, mask: u32 },
+}
+
+fn word_checks<L: WordLayout>(known: &[KnownByte]) -> Vec<WordCheck> {
+    let mut words: Vec<(usize, u32, u32)> = Vec::new();
+    for (pos, k) in known {
+        let index = pos / 4;
+        let shift = L::byte_shift(pos % 4);
+        let i = match words.iter().position(|(j, _, _)| *j == index) {
+            Some(i) => i,
+            None => {
+                words.push((index, 0, 0));
+                words.len() - 1
+            }
+        };
+        words[i].1 |= (*k as u32) << shift;
+        words[i].2 |= 0xff << shift;
+    }
+    words
+        .into_iter()
+        .map(|(index, value, mask)| {
+            if mask == 0xffffffff {
+                let raw = mt19937::untemper(value);
+                WordCheck::Raw { index, raw }
+            } else {
+                WordCheck::Masked { index, value, mask }
+            }
+        })
+        .collect()
+}
+
+/// Like `recover_seed` for MT19937, but only computes the first few raw
+/// output words for each seed. Fast enough for all 2^32 seeds, as long as the
+/// known keystream is within the first `4 * FAST_OUTPUTS` bytes.
+///
+/// OBS: Only words known in full are untempered up front. For words with
+/// just some bytes known, every guess is tempered and masked instead, so
+/// those don't prune any cheaper.
+pub fn recover_mt_seed<L: WordLayout>(
+    known: &[KnownByte],
+    seeds: RangeInclusive<u32>,
+) -> Option<u32> {
+    let checks = word_checks::<L>(known);
+    let words = checks
+        .iter()
+        .map(|c| match c {
+            WordCheck::Raw { index, .. } => index + 1,
+            WordCheck::Masked { index, .. } => index + 1,
+        })
+        .max()?;
+    if words > FAST_OUTPUTS {
+        return recover_seed::<crate::code::MT19937, L>(known, seeds);
+    }
+    seeds.into_par_iter().find_any(|seed| {
+        let mut raw = [0u32; FAST_OUTPUTS];
+        mt19937::first_raw_outputs(*seed, &mut raw[..words]);
+        checks.iter().all(|c| match c {
+            WordCheck::Raw { index, raw: r } => raw[*index] == *r,
+            WordCheck::Masked { index, value, mask } => {
+                mt19937::temper(raw[*index]) & mask == *value
+            }
+        })
+    })
+}
+
+#[test]
+fn test_recover_mt_seed() {
+    use super::{BigEndian, LittleEndian};
+    use crate::code::MT19937;
+
+    let plain = b"xxxxxxxxxxxxxxxxxxxxAAAAAAAAAAAAAA";
+    let offset = 20 - 3;
+    let mut cipher = plain.to_vec();
+    PrngStreamCipher::<MT19937, BigEndian>::new(1_000_123).apply(&mut cipher);
+    let known = known_keystream(&cipher, offset, &plain[offset..]);
+    let seeds = 1_000_000..=1_001_000;
+    let fast = recover_mt_seed::<BigEndian>(&known, seeds.clone());
+    let slow = recover_seed::<MT19937, BigEndian>(&known, seeds.clone());
+    let wrong_layout = recover_mt_seed::<LittleEndian>(&known, seeds);
+    assert!(fast == Some(1_000_123));
+    assert!(slow == Some(1_000_123));
+    assert!(wrong_layout.is_none());
+}
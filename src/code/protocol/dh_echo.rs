@@ -0,0 +1,168 @@
+//! The Diffie-Hellman echo protocol from challenges 34 and 35.
+//!
+//! The client agrees on a key with the echo bot, then sends messages
+//! encrypted with `dh::encrypt`. The bot decrypts each one and sends it
+//! back under a fresh IV.
+
+use super::{Closed, Endpoint};
+use crate::code;
+use code::bigint::BigUint;
+use code::dh::{self, Group, KeyPair};
+
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Msg {
+    /// p, g and A in one go, challenge 34
+    Hello { group: Group, public: BigUint },
+    /// p and g alone, challenge 35
+    Negotiate(Group),
+    /// The group the server went with
+    Ack(Group),
+    /// A or B
+    Public(BigUint),
+    /// `dh::encrypt` under the session key
+    Data(Vec<u8>),
+}
+
+/// How the client starts
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Handshake {
+    /// Hello, then the server's public key
+    Combined,
+    /// Negotiate, Ack, then public keys both ways
+    Negotiated,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    Closed,
+    /// A message that doesn't belong at this point
+    Unexpected(Msg),
+    /// Data that doesn't decrypt under our key
+    Decrypt,
+}
+
+impl From<Closed> for Error {
+    fn from(_: Closed) -> Self {
+        Error::Closed
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Closed => write!(f, "Channel closed"),
+            Error::Unexpected(msg) => write!(f, "Unexpected {:?}", msg),
+            Error::Decrypt => write!(f, "Bad ciphertext"),
+        }
+    }
+}
+
+fn expect_public(ep: &mut Endpoint<Msg>) -> Result<BigUint, Error> {
+    match ep.recv()? {
+        Msg::Public(public) => Ok(public),
+        other => Err(Error::Unexpected(other)),
+    }
+}
+
+/// Sends every message in `messages` and waits for its echo. Returns the
+/// echoes, which should be the same.
+pub fn client(
+    mut ep: Endpoint<Msg>,
+    group: &Group,
+    handshake: Handshake,
+    messages: &[&[u8]],
+) -> Result<Vec<Vec<u8>>, Error> {
+    let (group, keys, other) = match handshake {
+        Handshake::Combined => {
+            let keys = KeyPair::new(group);
+            let public = keys.public().clone();
+            ep.send(Msg::Hello {
+                group: group.clone(),
+                public,
+            });
+            let other = expect_public(&mut ep)?;
+            (group.clone(), keys, other)
+        }
+        Handshake::Negotiated => {
+            ep.send(Msg::Negotiate(group.clone()));
+            // The server's choice wins
+            let group = match ep.recv()? {
+                Msg::Ack(group) => group,
+                other => return Err(Error::Unexpected(other)),
+            };
+            let keys = KeyPair::new(&group);
+            ep.send(Msg::Public(keys.public().clone()));
+            let other = expect_public(&mut ep)?;
+            (group, keys, other)
+        }
+    };
+    let key = dh::session_key(&keys.shared(&group, &other));
+
+    let mut echoes = Vec::with_capacity(messages.len());
+    for plain in messages {
+        ep.send(Msg::Data(dh::encrypt(&key, plain)));
+        match ep.recv()? {
+            Msg::Data(cipher) => {
+                echoes.push(dh::decrypt(&key, &cipher).ok_or(Error::Decrypt)?)
+            }
+            other => return Err(Error::Unexpected(other)),
+        }
+    }
+    Ok(echoes)
+}
+
+/// Takes either handshake, then echoes until the client is done. Returns
+/// what it was sent.
+pub fn echo_bot(mut ep: Endpoint<Msg>) -> Result<Vec<Vec<u8>>, Error> {
+    let (group, keys, other) = match ep.recv()? {
+        Msg::Hello { group, public } => {
+            let keys = KeyPair::new(&group);
+            ep.send(Msg::Public(keys.public().clone()));
+            (group, keys, public)
+        }
+        Msg::Negotiate(group) => {
+            ep.send(Msg::Ack(group.clone()));
+            let other = expect_public(&mut ep)?;
+            let keys = KeyPair::new(&group);
+            ep.send(Msg::Public(keys.public().clone()));
+            (group, keys, other)
+        }
+        other => return Err(Error::Unexpected(other)),
+    };
+    let key = dh::session_key(&keys.shared(&group, &other));
+
+    let mut received = Vec::new();
+    loop {
+        let cipher = match ep.recv() {
+            Ok(Msg::Data(cipher)) => cipher,
+            Ok(other) => return Err(Error::Unexpected(other)),
+            Err(Closed) => return Ok(received),
+        };
+        let plain = dh::decrypt(&key, &cipher).ok_or(Error::Decrypt)?;
+        ep.send(Msg::Data(dh::encrypt(&key, &plain)));
+        received.push(plain);
+    }
+}
+
+#[test]
+fn test_echo() {
+    use super::{run, Passive};
+
+    let group = Group::default();
+    let messages: &[&[u8]] = &[b"Hello", b"", b"YELLOW SUBMARINE!"];
+    for handshake in &[Handshake::Combined, Handshake::Negotiated] {
+        let client = |ep| client(ep, &group, *handshake, messages);
+        let (echoes, received, log) = run(client, echo_bot, &mut Passive);
+        assert_eq!(echoes.unwrap(), messages);
+        assert_eq!(received.unwrap(), messages);
+        let handshake_len = match handshake {
+            Handshake::Combined => 2,
+            Handshake::Negotiated => 4,
+        };
+        assert_eq!(log.len(), handshake_len + 2 * messages.len());
+    }
+}
@@ -0,0 +1,238 @@
+//! Two parties talking over in-memory channels, with a man in the middle.
+//!
+//! Each party runs on its own thread with an `Endpoint`. Everything sent
+//! passes through a `Mitm` on the calling thread, which can read, drop,
+//! rewrite or replay it. A plain `Passive` forwards everything.
+//!
+//! When both parties wait with nothing in flight, or one waits for one
+//! that is done, nothing more can arrive and `Endpoint::recv` returns
+//! `Closed`. So a dropped message ends the run instead of hanging it.
+
+use std::error::Error;
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+pub mod dh_echo;
+pub mod srp;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Client,
+    Server,
+}
+
+impl Side {
+    pub fn other(self) -> Self {
+        match self {
+            Side::Client => Side::Server,
+            Side::Server => Side::Client,
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Nothing more will arrive
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Closed;
+
+impl Error for Closed {}
+
+impl fmt::Display for Closed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Channel closed")
+    }
+}
+
+enum Event<M> {
+    Sent(Side, M),
+    /// Blocked in `recv`, having received this many
+    Waiting(Side, usize),
+    Done(Side),
+}
+
+/// A party's end of the channel
+pub struct Endpoint<M> {
+    side: Side,
+    events: Sender<Event<M>>,
+    inbox: Receiver<M>,
+    received: usize,
+}
+
+impl<M> Endpoint<M> {
+    pub fn side(&self) -> Side {
+        self.side
+    }
+
+    pub fn send(&self, msg: M) {
+        // The middle outlives the endpoints
+        let _ = self.events.send(Event::Sent(self.side, msg));
+    }
+
+    pub fn recv(&mut self) -> Result<M, Closed> {
+        let _ = self.events.send(Event::Waiting(self.side, self.received));
+        let msg = self.inbox.recv().map_err(|_| Closed)?;
+        self.received += 1;
+        Ok(msg)
+    }
+}
+
+impl<M> Drop for Endpoint<M> {
+    fn drop(&mut self) {
+        let _ = self.events.send(Event::Done(self.side));
+    }
+}
+
+pub trait Mitm<M> {
+    /// What to deliver instead of `msg` from `from`. Nothing drops it,
+    /// several replay or inject.
+    fn intercept(&mut self, from: Side, msg: M) -> Vec<M>;
+}
+
+/// Forwards everything untouched
+pub struct Passive;
+
+impl<M> Mitm<M> for Passive {
+    fn intercept(&mut self, _from: Side, msg: M) -> Vec<M> {
+        vec![msg]
+    }
+}
+
+impl<M, F: FnMut(Side, M) -> Vec<M>> Mitm<M> for F {
+    fn intercept(&mut self, from: Side, msg: M) -> Vec<M> {
+        self(from, msg)
+    }
+}
+
+/// One message as sent, and what the other side got instead
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry<M> {
+    pub from: Side,
+    pub sent: M,
+    pub delivered: Vec<M>,
+}
+
+/// Runs `client` and `server` until both return, everything between them
+/// going through `mitm`. Panics if either does.
+pub fn run<M, C, S, RC, RS>(
+    client: C,
+    server: S,
+    mitm: &mut dyn Mitm<M>,
+) -> (RC, RS, Vec<Entry<M>>)
+where
+    M: Clone + Send,
+    C: FnOnce(Endpoint<M>) -> RC + Send,
+    S: FnOnce(Endpoint<M>) -> RS + Send,
+    RC: Send,
+    RS: Send,
+{
+    let (events_tx, events) = mpsc::channel();
+    let (client_tx, client_rx) = mpsc::channel();
+    let (server_tx, server_rx) = mpsc::channel();
+    let endpoint = |side, inbox| Endpoint {
+        side,
+        events: events_tx.clone(),
+        inbox,
+        received: 0,
+    };
+    let client_end = endpoint(Side::Client, client_rx);
+    let server_end = endpoint(Side::Server, server_rx);
+    drop(events_tx);
+
+    thread::scope(|scope| {
+        let client = scope.spawn(move || client(client_end));
+        let server = scope.spawn(move || server(server_end));
+
+        let mut inboxes = [Some(client_tx), Some(server_tx)];
+        let mut delivered = [0; 2];
+        let mut waiting = [None; 2];
+        let mut done = [false; 2];
+        let mut transcript = Vec::new();
+        // Ends when both endpoints are dropped
+        while let Ok(event) = events.recv() {
+            match event {
+                Event::Sent(from, msg) => {
+                    let out = mitm.intercept(from, msg.clone());
+                    let to = from.other().index();
+                    for m in &out {
+                        let inbox = inboxes[to].as_ref();
+                        if inbox.is_some_and(|tx| tx.send(m.clone()).is_ok()) {
+                            delivered[to] += 1;
+                        }
+                    }
+                    transcript.push(Entry {
+                        from,
+                        sent: msg,
+                        delivered: out,
+                    });
+                }
+                Event::Waiting(side, received) => {
+                    waiting[side.index()] = Some(received)
+                }
+                Event::Done(side) => done[side.index()] = true,
+            }
+            let stuck = |i: usize| done[i] || waiting[i] == Some(delivered[i]);
+            if stuck(0) && stuck(1) {
+                inboxes = [None, None];
+            }
+        }
+        let client = client.join().expect("Client panicked");
+        let server = server.join().expect("Server panicked");
+        (client, server, transcript)
+    })
+}
+
+#[test]
+fn test_mitm() {
+    // Server answers every number with its double, until closed
+    let server = |mut ep: Endpoint<u32>| {
+        let mut seen = Vec::new();
+        while let Ok(x) = ep.recv() {
+            seen.push(x);
+            ep.send(2 * x);
+        }
+        seen
+    };
+    let client = |mut ep: Endpoint<u32>| {
+        (1..=3)
+            .map(|x| {
+                ep.send(x);
+                ep.recv()
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let (got, seen, log) = run(client, server, &mut Passive);
+    assert_eq!(got, vec![Ok(2), Ok(4), Ok(6)]);
+    assert_eq!(seen, vec![1, 2, 3]);
+    assert_eq!(log.len(), 6);
+
+    // Rewrites 2 to 10, drops the answer to 3 and replays the first one
+    let mut first = None;
+    let mut mitm = |from: Side, x: u32| match (from, x) {
+        (Side::Client, 2) => vec![10],
+        (Side::Client, _) => vec![x],
+        (Side::Server, 6) => vec![],
+        (Side::Server, _) => {
+            let replay = first.get_or_insert(x);
+            if *replay == x {
+                vec![x]
+            } else {
+                vec![x, *replay]
+            }
+        }
+    };
+    let (got, seen, log) = run(client, server, &mut mitm);
+    assert_eq!(got, vec![Ok(2), Ok(20), Ok(2)]);
+    assert_eq!(seen, vec![1, 10, 3]);
+    assert_eq!(log[3].delivered, vec![20, 2]);
+
+    // Dropping the first message closes both sides
+    let mut drop_all = |_: Side, _: u32| vec![];
+    let (got, seen, _) = run(client, server, &mut drop_all);
+    assert_eq!(got, vec![Err(Closed); 3]);
+    assert!(seen.is_empty());
+}
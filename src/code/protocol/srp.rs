@@ -0,0 +1,294 @@
+//! Secure Remote Password, SRP-6a with SHA-256, challenges 36 to 38.
+//!
+//! The server only stores a salt and the verifier `v = g^x` for each
+//! user, where `x = H(salt | H(user ":" password))` as in RFC 5054. Both
+//! sides end up with the same S, and the client proves it knows
+//! `K = H(S)` with `HMAC(K, salt)`.
+//!
+//! `Variant::Simplified` is the one from challenge 38: `B = g^b`, and u
+//! is random and sent by the server rather than `H(A | B)`.
+
+use super::{Closed, Endpoint};
+use crate::code;
+use code::bigint::BigUint;
+use code::dh::{Group, KeyPair};
+use code::hash::sha256::{Sha256, Sha256Core};
+use code::hash::Hmac;
+
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Variant {
+    Srp6a,
+    Simplified,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Msg {
+    /// I and A
+    Hello { user: String, public: BigUint },
+    /// The salt and B, and u in simplified SRP
+    Challenge {
+        salt: Vec<u8>,
+        public: BigUint,
+        u: Option<BigUint>,
+    },
+    /// HMAC-SHA256(K, salt)
+    Proof(Vec<u8>),
+    /// Whether the proof checked out
+    Verdict(bool),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    Closed,
+    /// A message that doesn't belong at this point
+    Unexpected(Msg),
+    UnknownUser(String),
+    /// A or B is 0 mod N
+    BadPublic,
+    /// The client's proof was wrong
+    WrongProof,
+    /// The server said no
+    Rejected,
+    /// The server sent u when it shouldn't have, or didn't when it should
+    WrongVariant,
+}
+
+impl From<Closed> for Error {
+    fn from(_: Closed) -> Self {
+        Error::Closed
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Closed => write!(f, "Channel closed"),
+            Error::Unexpected(msg) => write!(f, "Unexpected {:?}", msg),
+            Error::UnknownUser(user) => write!(f, "Unknown user {:?}", user),
+            Error::BadPublic => write!(f, "Public key is 0 mod N"),
+            Error::WrongProof => write!(f, "Wrong proof"),
+            Error::Rejected => write!(f, "Login rejected"),
+            Error::WrongVariant => write!(f, "Server uses another variant"),
+        }
+    }
+}
+
+/// SHA-256 of everything in `parts`, as a number
+fn hash(parts: &[&[u8]]) -> BigUint {
+    let mut h = Sha256::new();
+    parts.iter().for_each(|p| h.update(p));
+    BigUint::from_bytes_be(&h.finalize())
+}
+
+/// `x` reduced mod N and left padded to the length of N
+fn pad(group: &Group, x: &BigUint) -> Vec<u8> {
+    (x % &group.p).to_bytes_be_padded(group.element_len())
+}
+
+/// k = H(N | PAD(g))
+pub fn multiplier(group: &Group) -> BigUint {
+    hash(&[&group.p.to_bytes_be(), &pad(group, &group.g)])
+}
+
+/// u = H(PAD(A) | PAD(B)), for SRP-6a
+pub fn scrambler(group: &Group, a: &BigUint, b: &BigUint) -> BigUint {
+    hash(&[&pad(group, a), &pad(group, b)])
+}
+
+/// x = H(salt | H(user ":" password))
+pub fn private_key(salt: &[u8], user: &str, password: &str) -> BigUint {
+    let inner = Sha256::digest(format!("{}:{}", user, password).as_bytes());
+    hash(&[salt, &inner])
+}
+
+/// HMAC-SHA256(H(S), salt), what the client sends to log in
+pub fn proof(secret: &BigUint, salt: &[u8]) -> Vec<u8> {
+    let key = Sha256::digest(&secret.to_bytes_be());
+    Vec::from(&Hmac::<Sha256Core>::mac(&key, salt)[..])
+}
+
+/// Logs in as `user`. Ok if the server accepted the proof.
+pub fn client(
+    mut ep: Endpoint<Msg>,
+    group: &Group,
+    variant: Variant,
+    user: &str,
+    password: &str,
+) -> Result<(), Error> {
+    let keys = KeyPair::new(group);
+    ep.send(Msg::Hello {
+        user: user.to_string(),
+        public: keys.public().clone(),
+    });
+    let (salt, b, u) = match ep.recv()? {
+        Msg::Challenge { salt, public, u } => (salt, public, u),
+        // Turned away before the challenge, like an unknown user
+        Msg::Verdict(false) => return Err(Error::Rejected),
+        other => return Err(Error::Unexpected(other)),
+    };
+    let u = match (variant, u) {
+        (Variant::Srp6a, None) => scrambler(group, keys.public(), &b),
+        (Variant::Simplified, Some(u)) => u,
+        _ => return Err(Error::WrongVariant),
+    };
+    if (&b % &group.p).is_zero() {
+        return Err(Error::BadPublic);
+    }
+
+    let x = private_key(&salt, user, password);
+    let n = &group.p;
+    // S = (B - k g^x)^(a + u x), without the k g^x when simplified
+    let base = match variant {
+        Variant::Srp6a => {
+            let kv = &multiplier(group) * &group.g.modpow(&x, n);
+            b.sub_mod(&kv, n)
+        }
+        Variant::Simplified => b,
+    };
+    let exp = keys.private() + &(&u * &x);
+    let secret = base.modpow(&exp, n);
+    ep.send(Msg::Proof(proof(&secret, &salt)));
+    match ep.recv()? {
+        Msg::Verdict(true) => Ok(()),
+        Msg::Verdict(false) => Err(Error::Rejected),
+        other => Err(Error::Unexpected(other)),
+    }
+}
+
+struct Verifier {
+    salt: Vec<u8>,
+    v: BigUint,
+}
+
+/// Knows the users' verifiers, not their passwords
+pub struct Server {
+    group: Group,
+    variant: Variant,
+    check_public: bool,
+    users: HashMap<String, Verifier>,
+}
+
+impl Server {
+    pub fn new(group: Group, variant: Variant) -> Self {
+        Self {
+            group,
+            variant,
+            check_public: true,
+            users: HashMap::new(),
+        }
+    }
+
+    /// Whether to refuse A = 0 mod N, as SRP-6a requires. On by default,
+    /// off to be broken like challenge 37.
+    pub fn with_public_check(mut self, check: bool) -> Self {
+        self.check_public = check;
+        self
+    }
+
+    pub fn group(&self) -> &Group {
+        &self.group
+    }
+
+    pub fn register(&mut self, user: &str, password: &str) {
+        let salt = code::rnd(16);
+        let x = private_key(&salt, user, password);
+        let v = self.group.g.modpow(&x, &self.group.p);
+        self.users.insert(user.to_string(), Verifier { salt, v });
+    }
+
+    /// Handles one login. Ok with the user if it succeeded.
+    pub fn serve(&self, mut ep: Endpoint<Msg>) -> Result<String, Error> {
+        let res = self.login(&mut ep);
+        ep.send(Msg::Verdict(res.is_ok()));
+        res
+    }
+
+    fn login(&self, ep: &mut Endpoint<Msg>) -> Result<String, Error> {
+        let (user, a) = match ep.recv()? {
+            Msg::Hello { user, public } => (user, public),
+            other => return Err(Error::Unexpected(other)),
+        };
+        let Verifier { salt, v } = self
+            .users
+            .get(&user)
+            .ok_or_else(|| Error::UnknownUser(user.clone()))?;
+        let n = &self.group.p;
+        if self.check_public && (&a % n).is_zero() {
+            return Err(Error::BadPublic);
+        }
+
+        let keys = KeyPair::new(&self.group);
+        let (b, u) = match self.variant {
+            Variant::Srp6a => {
+                // B = k v + g^b
+                let kv = &multiplier(&self.group) * v;
+                let b = &(&kv + keys.public()) % n;
+                let u = scrambler(&self.group, &a, &b);
+                ep.send(Msg::Challenge {
+                    salt: salt.clone(),
+                    public: b.clone(),
+                    u: None,
+                });
+                (b, u)
+            }
+            Variant::Simplified => {
+                let u = BigUint::from_bytes_be(&code::rnd(16));
+                ep.send(Msg::Challenge {
+                    salt: salt.clone(),
+                    public: keys.public().clone(),
+                    u: Some(u.clone()),
+                });
+                (keys.public().clone(), u)
+            }
+        };
+        if b.is_zero() {
+            // Astronomically unlikely, but the client would refuse it
+            return Err(Error::BadPublic);
+        }
+
+        // S = (A v^u)^b
+        let secret = (&a * &v.modpow(&u, n)).modpow(keys.private(), n);
+        match ep.recv()? {
+            Msg::Proof(mac) => {
+                let expected = proof(&secret, salt);
+                if code::hash::hmac::constant_time_eq(&mac, &expected) {
+                    Ok(user)
+                } else {
+                    Err(Error::WrongProof)
+                }
+            }
+            other => Err(Error::Unexpected(other)),
+        }
+    }
+}
+
+#[test]
+fn test_login() {
+    use super::{run, Passive};
+
+    for variant in &[Variant::Srp6a, Variant::Simplified] {
+        let mut server = Server::new(Group::default(), *variant);
+        server.register("alice", "hunter2");
+        let group = server.group().clone();
+        let login = |password: &'static str| {
+            let client = |ep| client(ep, &group, *variant, "alice", password);
+            let (client, server, _) =
+                run(client, |ep| server.serve(ep), &mut Passive);
+            (client, server)
+        };
+        assert_eq!(login("hunter2"), (Ok(()), Ok("alice".to_string())));
+        assert_eq!(
+            login("hunter3"),
+            (Err(Error::Rejected), Err(Error::WrongProof))
+        );
+        let client = |ep| client(ep, &group, *variant, "mallory", "x");
+        let (res, user, _) = run(client, |ep| server.serve(ep), &mut Passive);
+        assert_eq!(res, Err(Error::Rejected));
+        assert_eq!(user, Err(Error::UnknownUser("mallory".to_string())));
+    }
+}
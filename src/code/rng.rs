@@ -0,0 +1,74 @@
+//! The crate wide source of randomness.
+//!
+//! Seeded from the `CRYPTOPALS_SEED` env var if set. Otherwise a random seed
+//! is picked and printed, so a failing run can be replayed exactly by
+//! setting the env var to it.
+
+use lazy_static::*;
+use rand::distributions::{Distribution, Standard, Uniform};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::sync::Mutex;
+
+pub const SEED_VAR: &str = "CRYPTOPALS_SEED";
+
+struct Seeded {
+    seed: u64,
+    rng: StdRng,
+}
+
+impl Seeded {
+    fn new(seed: u64) -> Self {
+        let rng = StdRng::seed_from_u64(seed);
+        Self { seed, rng }
+    }
+}
+
+fn initial_seed() -> u64 {
+    match std::env::var(SEED_VAR) {
+        Ok(s) => s.trim().parse().expect("CRYPTOPALS_SEED is not a u64"),
+        Err(_) => {
+            let seed = rand::random();
+            eprintln!("Using random seed {}={}", SEED_VAR, seed);
+            seed
+        }
+    }
+}
+
+lazy_static! {
+    static ref RNG: Mutex<Seeded> = Mutex::new(Seeded::new(initial_seed()));
+}
+
+/// The seed the current sequence started from
+pub fn seed() -> u64 {
+    RNG.lock().unwrap().seed
+}
+
+/// Restarts from `seed`
+pub fn reseed(seed: u64) {
+    *RNG.lock().unwrap() = Seeded::new(seed);
+}
+
+pub fn with<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut StdRng) -> T,
+{
+    f(&mut RNG.lock().unwrap().rng)
+}
+
+/// Like `rand::random`
+pub fn random<T>() -> T
+where
+    Standard: Distribution<T>,
+{
+    with(|rng| Standard.sample(rng))
+}
+
+pub fn bytes(len: usize) -> Vec<u8> {
+    with(|rng| (0..len).map(|_| Standard.sample(rng)).collect())
+}
+
+/// Uniform in [min, max]
+pub fn interval(min: usize, max: usize) -> usize {
+    with(|rng| Uniform::new_inclusive(min, max).sample(rng))
+}
@@ -0,0 +1,136 @@
+//! Sources of time.
+//!
+//! Anything time based (token generators, timing leaks) takes a
+//! `TimeSource`, so it can run against a simulated clock. Then attacks on it
+//! are reproducible and don't have to actually sleep.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+pub trait TimeSource {
+    /// Milliseconds since the unix epoch
+    fn now_ms(&self) -> u64;
+
+    /// Lets `ms` milliseconds pass
+    fn sleep_ms(&self, ms: u64);
+
+    /// Microseconds since the unix epoch
+    fn now_us(&self) -> u64 {
+        1000 * self.now_ms()
+    }
+
+    /// Lets `us` microseconds pass. By default only whole milliseconds.
+    fn sleep_us(&self, us: u64) {
+        self.sleep_ms(us / 1000);
+    }
+
+    /// Seconds since the unix epoch, like a unix timestamp
+    fn timestamp(&self) -> u32 {
+        (self.now_ms() / 1000) as u32
+    }
+}
+
+fn system_us() -> u64 {
+    let t = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("What year is it?");
+    t.as_micros() as u64
+}
+
+/// The real time, sleeping actually sleeps
+pub struct WallClock;
+
+impl TimeSource for WallClock {
+    fn now_ms(&self) -> u64 {
+        system_us() / 1000
+    }
+
+    fn sleep_ms(&self, ms: u64) {
+        std::thread::sleep(Duration::from_millis(ms));
+    }
+
+    fn now_us(&self) -> u64 {
+        system_us()
+    }
+
+    fn sleep_us(&self, us: u64) {
+        std::thread::sleep(Duration::from_micros(us));
+    }
+}
+
+/// Simulated time, sleeping just moves the clock forward.
+/// Clones share the same time, so oracles and attackers can all use one.
+#[derive(Clone)]
+pub struct SimClock(Arc<AtomicU64>);
+
+impl SimClock {
+    pub fn at_ms(ms: u64) -> Self {
+        SimClock(Arc::new(AtomicU64::new(1000 * ms)))
+    }
+
+    /// Starts at the current real time
+    pub fn now() -> Self {
+        SimClock(Arc::new(AtomicU64::new(system_us())))
+    }
+
+    /// Starts at a random time from `code::rng`, so runs replay with
+    /// `CRYPTOPALS_SEED`. Far enough below 2^32 s for u32 timestamps.
+    pub fn seeded() -> Self {
+        let secs = crate::code::rng::interval(0, i32::MAX as usize);
+        Self::at_ms(1000 * secs as u64)
+    }
+}
+
+/// Keeps microseconds
+impl TimeSource for SimClock {
+    fn now_ms(&self) -> u64 {
+        self.now_us() / 1000
+    }
+
+    fn sleep_ms(&self, ms: u64) {
+        self.sleep_us(1000 * ms);
+    }
+
+    fn now_us(&self) -> u64 {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    fn sleep_us(&self, us: u64) {
+        self.0.fetch_add(us, Ordering::SeqCst);
+    }
+}
+
+/// Random waits, from a seed so runs can be repeated
+pub struct Jitter(StdRng);
+
+impl Jitter {
+    pub fn new(seed: u64) -> Self {
+        Jitter(StdRng::seed_from_u64(seed))
+    }
+
+    /// Random number of ms, both inclusive
+    pub fn ms(&mut self, min: u64, max: u64) -> u64 {
+        self.0.gen_range(min, max + 1)
+    }
+
+    /// Waits a random number of ms, both inclusive
+    pub fn wait_ms(&mut self, clock: &dyn TimeSource, min: u64, max: u64) {
+        let ms = self.ms(min, max);
+        clock.sleep_ms(ms);
+    }
+
+    /// Waits a random number of µs, both inclusive
+    pub fn wait_us(&mut self, clock: &dyn TimeSource, min: u64, max: u64) {
+        let us = self.0.gen_range(min, max + 1);
+        clock.sleep_us(us);
+    }
+
+    /// Waits a random number of whole seconds, up to 9
+    pub fn waitsome(&mut self, clock: &dyn TimeSource) {
+        let s = self.ms(0, 9);
+        clock.sleep_ms(1000 * s);
+    }
+}
@@ -0,0 +1,205 @@
+//! xorshift128+ as used by V8 for `Math.random`.
+//!
+//! Like V8, outputs are taken from `state0` alone and not from the sum
+//! `state0 + state1`, which keeps every output bit linear over GF(2) in the
+//! initial state. So the state can be solved for from truncated outputs.
+
+use crate::code::gf2::{BitRow, LinearSystem};
+
+use std::fmt;
+
+#[derive(Clone, Debug)]
+pub struct Xorshift128Plus {
+    state0: u64,
+    state1: u64,
+}
+
+impl Xorshift128Plus {
+    pub fn new(state0: u64, state1: u64) -> Self {
+        assert!(state0 != 0 || state1 != 0);
+        Self { state0, state1 }
+    }
+
+    pub fn state(&self) -> (u64, u64) {
+        (self.state0, self.state1)
+    }
+
+    /// V8's `XorShift128`
+    fn step(&mut self) {
+        let mut s1 = self.state0;
+        let s0 = self.state1;
+        self.state0 = s0;
+        s1 ^= s1 << 23;
+        s1 ^= s1 >> 17;
+        s1 ^= s0;
+        s1 ^= s0 >> 26;
+        self.state1 = s1;
+    }
+
+    /// Steps and returns the new `state0`
+    pub fn next_state0(&mut self) -> u64 {
+        self.step();
+        self.state0
+    }
+
+    /// V8's `ToDouble`, the top 52 bits of `state0` as the mantissa of a
+    /// double in [1, 2), minus 1
+    pub fn next_double(&mut self) -> f64 {
+        let bits = (self.next_state0() >> 12) | 0x3FF0000000000000;
+        f64::from_bits(bits) - 1.0
+    }
+}
+
+impl Iterator for Xorshift128Plus {
+    type Item = u32;
+    /// Top 32 bits of `state0`
+    fn next(&mut self) -> Option<u32> {
+        Some((self.next_state0() >> 32) as u32)
+    }
+}
+
+/// Some known bits of `state0` after step number `index` (from 0).
+/// Only bits set in `mask` are used from `value`.
+#[derive(Clone, Copy, Debug)]
+pub struct Observation {
+    pub index: usize,
+    pub value: u64,
+    pub mask: u64,
+}
+
+impl Observation {
+    /// Output of the `Iterator`
+    pub fn from_u32(index: usize, out: u32) -> Self {
+        let value = (out as u64) << 32;
+        Self {
+            index,
+            value,
+            mask: 0xffffffff00000000,
+        }
+    }
+
+    /// Output of `next_double`.
+    /// OBS: V8 fills a cache of 64 doubles and hands them out last first, so
+    /// indices run backwards within every 64 consecutive `Math.random()`.
+    pub fn from_double(index: usize, d: f64) -> Self {
+        let mantissa = (d + 1.0).to_bits() & ((1 << 52) - 1);
+        Self {
+            index,
+            value: mantissa << 12,
+            mask: !0 << 12,
+        }
+    }
+}
+
+pub enum SolveError {
+    Inconsistent,
+    Underdetermined { free_bits: usize },
+}
+
+impl fmt::Debug for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SolveError::Inconsistent => write!(f, "Inconsistent"),
+            SolveError::Underdetermined { free_bits } => {
+                write!(f, "Underdetermined ({} free bits)", free_bits)
+            }
+        }
+    }
+}
+
+const VARS: usize = 128;
+
+/// Symbolic 64-bit word: `word[i]` is the set of initial state bits that xor
+/// together to bit `i` of the word.
+/// state0 is variables 0..64, state1 is 64..128.
+type Word = Vec<BitRow>;
+
+fn sym_xor(a: &mut Word, b: &Word) {
+    a.iter_mut().zip(b).for_each(|(x, y)| x.xor_with(y));
+}
+
+fn sym_shl(a: &Word, s: usize) -> Word {
+    (0..64)
+        .map(|i| {
+            if i >= s {
+                a[i - s].clone()
+            } else {
+                BitRow::zero(VARS)
+            }
+        })
+        .collect()
+}
+
+fn sym_shr(a: &Word, s: usize) -> Word {
+    (0..64)
+        .map(|i| {
+            if i + s < 64 {
+                a[i + s].clone()
+            } else {
+                BitRow::zero(VARS)
+            }
+        })
+        .collect()
+}
+
+/// Same as `Xorshift128Plus::step`
+fn sym_step(state0: &mut Word, state1: &mut Word) {
+    let mut s1 = state0.clone();
+    let s0 = state1.clone();
+    *state0 = s0.clone();
+    let t = sym_shl(&s1, 23);
+    sym_xor(&mut s1, &t);
+    let t = sym_shr(&s1, 17);
+    sym_xor(&mut s1, &t);
+    sym_xor(&mut s1, &s0);
+    sym_xor(&mut s1, &sym_shr(&s0, 26));
+    *state1 = s1;
+}
+
+/// Recovers the generator from before the first step
+pub fn crack(
+    observations: &[Observation],
+) -> Result<Xorshift128Plus, SolveError> {
+    let mut obs = observations.to_vec();
+    obs.sort_by_key(|o| o.index);
+
+    let mut sys = LinearSystem::new(VARS);
+    let mut state0: Word = (0..64).map(|i| BitRow::unit(VARS, i)).collect();
+    let mut state1: Word =
+        (0..64).map(|i| BitRow::unit(VARS, 64 + i)).collect();
+    let mut steps = 0;
+    for o in obs {
+        while steps <= o.index {
+            sym_step(&mut state0, &mut state1);
+            steps += 1;
+        }
+        for (i, row) in state0.iter().enumerate() {
+            if (o.mask >> i) & 1 == 1 {
+                sys.add(row.clone(), (o.value >> i) & 1 == 1);
+            }
+        }
+    }
+
+    let x = sys.solve().ok_or(SolveError::Inconsistent)?;
+    if sys.free_vars() > 0 {
+        let free_bits = sys.free_vars();
+        return Err(SolveError::Underdetermined { free_bits });
+    }
+    let word = |first: usize| {
+        (0..64)
+            .filter(|i| x.get(first + i))
+            .fold(0u64, |acc, i| acc | (1 << i))
+    };
+    Ok(Xorshift128Plus::new(word(0), word(64)))
+}
+
+#[test]
+fn test_crack_doubles() {
+    let mut rng = Xorshift128Plus::new(0x0123456789abcdef, 0xfedcba987654321);
+    let obs: Vec<_> = (0..5)
+        .map(|i| Observation::from_double(i, rng.next_double()))
+        .collect();
+    let mut cracked = crack(&obs).unwrap();
+    cracked.by_ref().take(5).for_each(drop);
+    assert!(cracked.take(100).eq(rng.take(100)));
+}
@@ -0,0 +1,356 @@
+use cryptopals::code;
+use code::analysis;
+use code::attacks::timing;
+use code::encoding::{self, Encoding};
+use code::oracles::{TimingOracle, TimingServer};
+use code::time::{SimClock, TimeSource, WallClock};
+
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::sync::Arc;
+
+mod set1;
+mod set2;
+mod set3;
+mod set4;
+mod set5;
+
+const USAGE: &str = "\
+Usage: cryptopals <command> [args]
+
+Commands:
+  list                        List all challenges and their extra demos
+  run <challenge> [demo]      Run a challenge, eg `run 23 truncated`
+  xor crack                   Crack repeating key xor
+      [--keysize N]           Otherwise guessed from hamming distances
+  aes cbc-decrypt --key HEX   Decrypt AES-128-CBC and remove the padding
+      [--iv HEX]              Defaults to all zeros
+  mt clone                    Clone MT19937 from 624 outputs (decimal)
+      [--count N]             Number of outputs to predict, default 10
+  ecb detect                  Print lines (hex) with a repeated block
+  analyze                     Statistics on unknown ciphertext
+  timing                      Recover an HMAC-SHA1 through its comparison
+      [--delay-us N]          Leak per matching byte, default 50
+      [--jitter-us N]         Random wait per request, default 0
+      [--clock sim|wall]      Simulated (default) or real time
+      [--via direct|http]     Call the oracle, or a local server (wall)
+
+Input is read from --file PATH, or stdin if not given.
+xor, aes and analyze take --format with any of raw, auto (guess), hex, hexdump,
+base64, base64url, base32, base58, ascii85 or percent. Default base64.";
+
+type Challenge = (usize, fn(), &'static [(&'static str, fn())]);
+
+const CHALLENGES: &[Challenge] = &[
+    (1, set1::challenge1::main, &[]),
+    (2, set1::challenge2::main, &[]),
+    (3, set1::challenge3::main, &[]),
+    (4, set1::challenge4::main, &[]),
+    (5, set1::challenge5::main, &[]),
+    (6, set1::challenge6::main, set1::challenge6::DEMOS),
+    (7, set1::challenge7::main, &[]),
+    (8, set1::challenge8::main, &[]),
+    (9, set2::challenge9::main, &[]),
+    (10, set2::challenge10::main, &[]),
+    (11, set2::challenge11::main, &[]),
+    (12, set2::challenge12::main, &[]),
+    (13, set2::challenge13::main, &[]),
+    (14, set2::challenge14::main, &[]),
+    (15, set2::challenge15::main, &[]),
+    (16, set2::challenge16::main, &[]),
+    (17, set3::challenge17::main, &[]),
+    (18, set3::challenge18::main, &[]),
+    (19, set3::challenge19::main, &[]),
+    (20, set3::challenge20::main, &[]),
+    (21, set3::challenge21::main, &[]),
+    (22, set3::challenge22::main, set3::challenge22::DEMOS),
+    (23, set3::challenge23::main, set3::challenge23::DEMOS),
+    (24, set3::challenge24::main, set3::challenge24::DEMOS),
+    (29, set4::challenge29::main, &[]),
+    (33, set5::challenge33::main, &[]),
+    (34, set5::challenge34::main, &[]),
+    (35, set5::challenge35::main, &[]),
+    (36, set5::challenge36::main, &[]),
+    (37, set5::challenge37::main, &[]),
+    (38, set5::challenge38::main, &[]),
+];
+
+/// Positional arguments and `--name value` options
+struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, String>,
+}
+
+impl Args {
+    fn parse<I>(mut args: I) -> Result<Self, String>
+    where
+        I: Iterator<Item = String>,
+    {
+        let mut positional = Vec::new();
+        let mut options = HashMap::new();
+        while let Some(arg) = args.next() {
+            if let Some(name) = arg.strip_prefix("--") {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("--{} needs a value", name))?;
+                options.insert(String::from(name), value);
+            } else {
+                positional.push(arg);
+            }
+        }
+        Ok(Self {
+            positional,
+            options,
+        })
+    }
+
+    fn pos(&self, i: usize) -> Option<&str> {
+        self.positional.get(i).map(|s| s.as_str())
+    }
+
+    fn opt(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(|s| s.as_str())
+    }
+
+    fn opt_num(&self, name: &str, default: usize) -> Result<usize, String> {
+        match self.opt(name) {
+            Some(n) => n
+                .parse()
+                .map_err(|_| format!("--{} is not a number: {}", name, n)),
+            None => Ok(default),
+        }
+    }
+
+    fn hex_opt(&self, name: &str) -> Result<Option<Vec<u8>>, String> {
+        self.opt(name)
+            .map(|h| {
+                encoding::hex::decode(h)
+                    .map_err(|e| format!("--{}: {}", name, e))
+            })
+            .transpose()
+    }
+
+    /// All of --file, or stdin
+    fn input(&self) -> Result<Vec<u8>, String> {
+        let mut buf = Vec::new();
+        match self.opt("file") {
+            Some(path) => {
+                buf = std::fs::read(path)
+                    .map_err(|e| format!("{}: {}", path, e))?;
+            }
+            None => {
+                io::stdin()
+                    .read_to_end(&mut buf)
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+        Ok(buf)
+    }
+
+    /// Input decoded according to --format
+    fn bytes(&self) -> Result<Vec<u8>, String> {
+        let inp = self.input()?;
+        let format = self.opt("format").unwrap_or("base64");
+        if format == "raw" {
+            return Ok(inp);
+        }
+        let text = String::from_utf8(inp).map_err(|e| e.to_string())?;
+        let enc = if format == "auto" {
+            let enc = encoding::detect(&text).ok_or("Unknown encoding")?;
+            eprintln!("Input looks like {}", enc.name());
+            enc
+        } else {
+            Encoding::from_name(format)
+                .ok_or_else(|| format!("Unknown format {}", format))?
+        };
+        enc.decode(&text).map_err(|e| e.to_string())
+    }
+}
+
+fn list() {
+    for (n, _, demos) in CHALLENGES {
+        let names: Vec<_> = demos.iter().map(|(name, _)| *name).collect();
+        println!("{}", format!("{:2} {}", n, names.join(" ")).trim_end());
+    }
+}
+
+fn run(args: &Args) -> Result<(), String> {
+    let n = args.pos(1).ok_or("Which challenge?")?;
+    let (_, main, demos) = CHALLENGES
+        .iter()
+        .find(|(i, _, _)| i.to_string() == n)
+        .ok_or_else(|| format!("No challenge {}", n))?;
+    match args.pos(2) {
+        None => main(),
+        Some(name) => {
+            let (_, demo) = demos
+                .iter()
+                .find(|(d, _)| *d == name)
+                .ok_or_else(|| format!("Challenge {} has no {}", n, name))?;
+            demo();
+        }
+    }
+    Ok(())
+}
+
+fn xor_crack(args: &Args) -> Result<(), String> {
+    use set1::challenge6::{crack_with_ksize, find_keysize};
+    let inp = args.bytes()?;
+    if inp.len() < 3 {
+        return Err(String::from("Too little input"));
+    }
+    let ksize = args.opt_num("keysize", 0)?;
+    let ksize = if ksize == 0 { find_keysize(&inp) } else { ksize };
+    let (key, plain) = crack_with_ksize(&inp, ksize);
+    eprintln!("Key ({} bytes): {}", key.len(), code::encode_hex(&key));
+    io::stdout().write_all(&plain).map_err(|e| e.to_string())
+}
+
+fn aes_cbc_decrypt(args: &Args) -> Result<(), String> {
+    let bs = code::aes128::BLOCKSIZE;
+    let key = args.hex_opt("key")?.ok_or("Missing --key")?;
+    let iv = args.hex_opt("iv")?.unwrap_or_else(|| vec![0; bs]);
+    if key.len() != bs || iv.len() != bs {
+        return Err(format!("Key and iv must be {} bytes", bs));
+    }
+    let mut buf = args.bytes()?;
+    if buf.is_empty() || buf.len() % bs != 0 {
+        return Err(format!("Input is not whole {} byte blocks", bs));
+    }
+    code::aes128::Aes128::new(&key).cbc_decr(&iv, &mut buf);
+    let plain = code::pkcs7_validate(&buf).ok_or("Bad padding")?;
+    io::stdout().write_all(plain).map_err(|e| e.to_string())
+}
+
+fn mt_clone(args: &Args) -> Result<(), String> {
+    let count = args.opt_num("count", 10)?;
+    let inp = String::from_utf8(args.input()?).map_err(|e| e.to_string())?;
+    let outputs = inp
+        .split_whitespace()
+        .map(|w| w.parse::<u32>().map_err(|_| format!("Bad output {}", w)))
+        .collect::<Result<Vec<_>, _>>()?;
+    if outputs.len() < 624 {
+        return Err(format!("Need 624 outputs, got {}", outputs.len()));
+    }
+    // Clone from the last 624. The clone first repeats them, then predicts
+    let last = &outputs[(outputs.len() - 624)..];
+    let clone = set3::challenge23::crack_state(last.iter().copied());
+    for x in clone.skip(624).take(count) {
+        println!("{}", x);
+    }
+    Ok(())
+}
+
+fn ecb_detect(args: &Args) -> Result<(), String> {
+    let inp = String::from_utf8(args.input()?).map_err(|e| e.to_string())?;
+    for (i, line) in inp.lines().enumerate() {
+        let bytes = encoding::hex::decode(line.trim())
+            .map_err(|e| format!("Line {}: {}", i + 1, e))?;
+        if let Some(block) = set1::challenge8::identify_ecb(&bytes) {
+            println!("{}: {} repeats {}", i + 1, line, code::encode_hex(block));
+        }
+    }
+    Ok(())
+}
+
+fn analyze(args: &Args) -> Result<(), String> {
+    let buf = args.bytes()?;
+    print!("{}", analysis::Report::new(&buf));
+    Ok(())
+}
+
+fn show_byte(b: &timing::ByteReport) {
+    eprintln!(
+        "{:2}: {:02x}  confidence {:6.1}  margin {:8.1}us  {:3} samples",
+        b.index, b.byte, b.confidence, b.margin_us, b.samples
+    );
+}
+
+fn noisy<C: TimeSource>(
+    oracle: TimingOracle<C>,
+    jitter: u64,
+) -> TimingOracle<C> {
+    match jitter {
+        0 => oracle,
+        _ => oracle.with_jitter(jitter, code::rng::random()),
+    }
+}
+
+fn timing_direct<C: TimeSource>(
+    attack: &timing::TimingAttack,
+    oracle: TimingOracle<C>,
+    file: &[u8],
+) -> Result<(timing::Recovery, Vec<u8>), String> {
+    let mut target = timing::Direct::new(&oracle, file);
+    let res = attack
+        .run(&mut target, show_byte)
+        .map_err(|e| e.to_string())?;
+    Ok((res, oracle.expected(file).to_vec()))
+}
+
+fn timing(args: &Args) -> Result<(), String> {
+    let delay = args.opt_num("delay-us", 50)? as u64;
+    let jitter = args.opt_num("jitter-us", 0)? as u64;
+    let file = b"foo";
+    let attack = timing::TimingAttack::new(code::hash::sha1::OUTPUT);
+    let clock = args.opt("clock").unwrap_or("sim");
+    let (res, expected) = match (clock, args.opt("via").unwrap_or("direct")) {
+        ("sim", "direct") => {
+            let oracle =
+                noisy(TimingOracle::new(SimClock::at_ms(0), delay), jitter);
+            timing_direct(&attack, oracle, file)?
+        }
+        ("wall", "direct") => {
+            let oracle = noisy(TimingOracle::new(WallClock, delay), jitter);
+            timing_direct(&attack, oracle, file)?
+        }
+        ("wall", "http") => {
+            let oracle = noisy(TimingOracle::new(WallClock, delay), jitter);
+            let oracle = Arc::new(oracle);
+            let server = TimingServer::spawn(oracle.clone())
+                .map_err(|e| e.to_string())?;
+            eprintln!("Listening on {}", server.addr());
+            let mut target = timing::Http::new(server.addr(), file);
+            let res = attack
+                .run(&mut target, show_byte)
+                .map_err(|e| e.to_string())?;
+            (res, oracle.expected(file).to_vec())
+        }
+        (_, "http") => return Err(String::from("http needs --clock wall")),
+        (clock, via) => {
+            return Err(format!("Unknown --clock {} or --via {}", clock, via))
+        }
+    };
+    println!("Recovered {}", code::encode_hex(&res.mac));
+    println!("Expected  {}", code::encode_hex(&expected));
+    println!(
+        "Accepted: {}, {} queries, {} backtracks",
+        res.valid, res.queries, res.backtracks
+    );
+    Ok(())
+}
+
+fn dispatch(args: &Args) -> Result<(), String> {
+    match (args.pos(0), args.pos(1)) {
+        (Some("list"), _) => {
+            list();
+            Ok(())
+        }
+        (Some("run"), _) => run(args),
+        (Some("xor"), Some("crack")) => xor_crack(args),
+        (Some("aes"), Some("cbc-decrypt")) => aes_cbc_decrypt(args),
+        (Some("mt"), Some("clone")) => mt_clone(args),
+        (Some("ecb"), Some("detect")) => ecb_detect(args),
+        (Some("analyze"), _) => analyze(args),
+        (Some("timing"), _) => timing(args),
+        _ => Err(String::from(USAGE)),
+    }
+}
+
+fn main() {
+    let res = Args::parse(std::env::args().skip(1)).and_then(|a| dispatch(&a));
+    if let Err(e) = res {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
@@ -1,6 +1,6 @@
 use cryptopals::code;
 
-fn main() {
+pub fn main() {
     let a = "1c0111001f010100061a024b53535009181c";
     let b = "686974207468652062756c6c277320657965";
     println!("{}\n{}", a, b);
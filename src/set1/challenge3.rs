@@ -2,7 +2,7 @@ use cryptopals::code;
 
 use code::english2;
 
-fn main() {
+pub fn main() {
     let hexin = concat!(
         "1b37373331363f78151b7f2b783431333d",
         "78397828372d363c78373e783a393b3736"
@@ -2,7 +2,7 @@ use cryptopals::code;
 
 use code::english2;
 
-fn main() {
+pub fn main() {
     let path = "challenge-data/4.txt";
     let inp = code::load_str(path);
 
@@ -1,6 +1,6 @@
 use cryptopals::code;
 
-fn main() {
+pub fn main() {
     let plain = "custom-data/eng-ascii1.txt";
     let key = "custom-data/rnd-bin-key-100";
 
@@ -33,7 +33,7 @@ fn eval_keysize(inp: &[u8], ksize: usize) -> f32 {
 }
 
 /// Return (key,plain)
-fn crack_with_ksize(inp: &[u8], ksize: usize) -> (Vec<u8>, Vec<u8>) {
+pub fn crack_with_ksize(inp: &[u8], ksize: usize) -> (Vec<u8>, Vec<u8>) {
     let mut plain = vec![0; inp.len()];
     let mut key = Vec::with_capacity(ksize);
     for chunk_idx in 0..ksize {
@@ -69,7 +69,7 @@ fn best_keysize(cands: &[(usize, f32)]) -> usize {
     return factors[0].0;
 }
 
-fn find_keysize(inp: &[u8]) -> usize {
+pub fn find_keysize(inp: &[u8]) -> usize {
     assert!(inp.len() > 2);
     let mut cands: Vec<_> = (1..(inp.len() / 2))
         .map(|ksize| (ksize, eval_keysize(inp, ksize)))
@@ -85,16 +85,15 @@ fn their_ex() -> Vec<u8> {
     return code::decode_base64(&inp);
 }
 
-#[allow(dead_code)]
 fn my_ex() -> Vec<u8> {
     let path = "custom-data/eng-ascii1-encr100.hex";
     let inp = code::load_str(path);
     return code::decode_hex(&inp);
 }
 
-fn main() {
-    let inp = their_ex();
-    //let inp = my_ex();
+pub const DEMOS: &[(&str, fn())] = &[("custom", demo_custom)];
+
+fn crack(inp: &[u8]) {
     let ksize = find_keysize(&inp);
     let (key, plain) = crack_with_ksize(&inp, ksize);
     let plain_str = std::str::from_utf8(&plain).unwrap();
@@ -103,3 +102,12 @@ fn main() {
         println!("Key as string: '{}'", key_str);
     }
 }
+
+/// Own ciphertext, from custom-data
+fn demo_custom() {
+    crack(&my_ex());
+}
+
+pub fn main() {
+    crack(&their_ex());
+}
@@ -1,6 +1,6 @@
 use cryptopals::code;
 
-fn main() {
+pub fn main() {
     let key = "YELLOW SUBMARINE".as_bytes();
     let cipher_b64 = code::load_str("challenge-data/7.txt");
     let mut buf = code::decode_base64(&cipher_b64);
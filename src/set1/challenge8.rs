@@ -1,9 +1,11 @@
 use cryptopals::code;
 
+use code::display::View;
+
 use std::collections::HashSet;
 
 /// If ecb, return a block that appears twice
-fn identify_ecb(inp: &[u8]) -> Option<&[u8]> {
+pub fn identify_ecb(inp: &[u8]) -> Option<&[u8]> {
     let mut blocks = HashSet::new();
     for block in inp.chunks_exact(code::aes128::BLOCKSIZE) {
         let new_block = blocks.insert(block);
@@ -14,13 +16,14 @@ fn identify_ecb(inp: &[u8]) -> Option<&[u8]> {
     return None;
 }
 
-fn main() {
+pub fn main() {
     let inp = code::load_str("challenge-data/8.txt");
     for l in inp.lines() {
         let bytes = code::decode_hex(l);
         if let Some(block) = identify_ecb(&bytes) {
             let blockhex = code::encode_hex(block);
             println!("{}\n->\n{}\n", l, blockhex);
+            print!("{}", View::new(code::aes128::BLOCKSIZE).blocks(&bytes));
         }
     }
 }
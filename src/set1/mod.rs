@@ -0,0 +1,8 @@
+pub mod challenge1;
+pub mod challenge2;
+pub mod challenge3;
+pub mod challenge4;
+pub mod challenge5;
+pub mod challenge6;
+pub mod challenge7;
+pub mod challenge8;
@@ -2,7 +2,7 @@ use cryptopals::code;
 
 use std::io::{Write,stdout};
 
-fn main() {
+pub fn main() {
     let key = "YELLOW SUBMARINE".as_bytes();
     let iv = [0u8; 16];
     let aes = code::aes128::Aes128::new(key);
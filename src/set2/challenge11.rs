@@ -1,7 +1,5 @@
 use cryptopals::code;
 
-use rand;
-
 // Generates between 5 and 10 random bytes
 fn some_rnd() -> Vec<u8> {
     let count = code::rnd_interval(5, 10);
@@ -20,7 +18,7 @@ fn rnd_encr(inp: &[u8]) -> Vec<u8> {
         .copied()
         .collect();
 
-    if rand::random() {
+    if code::rng::random() {
         // 50% chance of ecb
         println!("Encrypting with ECB");
         aes.ecb_encr(&mut buf);
@@ -34,7 +32,7 @@ fn rnd_encr(inp: &[u8]) -> Vec<u8> {
     return buf;
 }
 
-fn main() {
+pub fn main() {
     let plain = [0u8; 4*16];
     let cipher = rnd_encr(&plain);
     // Second and third block plain text will be all 0, regardless
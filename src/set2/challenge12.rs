@@ -1,4 +1,5 @@
 use cryptopals::code;
+use code::oracles::EcbSuffixOracle;
 
 use lazy_static::*;
 use std::iter::{once, repeat};
@@ -14,22 +15,15 @@ lazy_static! {
     static ref VICTIM_PLAIN: Vec<u8> = code::decode_base64(&TXT);
 }
 
-fn prepend_ecb(inp: &[u8]) -> Vec<u8> {
-    let mut buf: Vec<_> =
-        inp.iter().chain(VICTIM_PLAIN.iter()).copied().collect();
-    code::blackbox_aes().ecb_encr(&mut buf);
-    return buf;
-}
-
-fn find_blocksize() -> usize {
-    let mut lens = (0..).map(|l| prepend_ecb(&vec![0; l]).len());
+fn find_blocksize(oracle: &EcbSuffixOracle) -> usize {
+    let mut lens = (0..).map(|l| oracle.query(&vec![0; l]).len());
     let fst = lens.next().unwrap();
     let next = lens.find(|l| *l != fst).unwrap();
     return next - fst;
 }
 
-fn detect_ecb(blocksize: usize) -> bool {
-    let cipher = prepend_ecb(&vec![0; blocksize * 2]);
+fn detect_ecb(oracle: &EcbSuffixOracle, blocksize: usize) -> bool {
+    let cipher = oracle.query(&vec![0; blocksize * 2]);
     let fst = &cipher[0..blocksize];
     let snd = &cipher[blocksize..(2 * blocksize)];
     return code::cmp_blocks(fst, snd);
@@ -38,22 +32,27 @@ fn detect_ecb(blocksize: usize) -> bool {
 /// Gets the cipher block to compare against by prepending the right number of
 /// zeros to place the target byte at the last index of the block.
 fn get_target_cipher_block(
+    oracle: &EcbSuffixOracle,
     blocksize: usize,
     blk: usize,
     zero_count: usize,
 ) -> Vec<u8> {
     let prefix = vec![0u8; zero_count];
-    let cipher = prepend_ecb(&prefix);
+    let cipher = oracle.query(&prefix);
     return Vec::from(
         &cipher[(blk * blocksize)..(blk * blocksize + blocksize)],
     );
 }
 
-fn bruteforce_next(target: &[u8], block: &mut [u8]) -> u8 {
+fn bruteforce_next(
+    oracle: &EcbSuffixOracle,
+    target: &[u8],
+    block: &mut [u8],
+) -> u8 {
     let bs = target.len();
     for b in 0u8..=255u8 {
         block[bs - 1] = b;
-        let res = &prepend_ecb(&block)[0..bs];
+        let res = &oracle.query(&block)[0..bs];
         if code::cmp_blocks(&target, res) {
             return b;
         }
@@ -61,7 +60,7 @@ fn bruteforce_next(target: &[u8], block: &mut [u8]) -> u8 {
     unreachable!();
 }
 
-fn crack_next(blocksize: usize, plain: &[u8]) -> u8 {
+fn crack_next(oracle: &EcbSuffixOracle, blocksize: usize, plain: &[u8]) -> u8 {
     // We know first k bytes
     let k = plain.len();
     // target byte is in block blk at index i
@@ -70,7 +69,7 @@ fn crack_next(blocksize: usize, plain: &[u8]) -> u8 {
     // By prepending this number of zeros the target plain byte
     // will be placed as the last byte in blk.
     let zero_count = blocksize - i - 1;
-    let target = get_target_cipher_block(blocksize, blk, zero_count);
+    let target = get_target_cipher_block(oracle, blocksize, blk, zero_count);
     assert!(target.len() == blocksize);
     let mut block: Vec<_> = if k < blocksize - 1 {
         // This should add up to exactly one block
@@ -88,18 +87,19 @@ fn crack_next(blocksize: usize, plain: &[u8]) -> u8 {
             .collect()
     };
     assert!(block.len() == blocksize);
-    return bruteforce_next(&target, &mut block);
+    return bruteforce_next(oracle, &target, &mut block);
 }
 
-fn main() {
-    let blocksize = find_blocksize();
+pub fn main() {
+    let oracle = EcbSuffixOracle::new(&VICTIM_PLAIN);
+    let blocksize = find_blocksize(&oracle);
     println!("Blocksize = {}", blocksize);
 
-    assert!(detect_ecb(blocksize));
+    assert!(detect_ecb(&oracle, blocksize));
 
     let mut plain = vec![];
     while plain.len() < VICTIM_PLAIN.len() {
-        let next_plain = crack_next(blocksize, &plain);
+        let next_plain = crack_next(&oracle, blocksize, &plain);
         plain.push(next_plain);
         let s = String::from_utf8(plain.clone()).unwrap();
         println!("Found {} plain bytes so far: [{}]\n", plain.len(), s);
@@ -107,4 +107,5 @@ fn main() {
 
     let s = String::from_utf8(plain).unwrap();
     println!("Final result:\n{}", s);
+    println!("Using {} queries", oracle.queries());
 }
@@ -1,79 +1,20 @@
 use cryptopals::code;
+use code::oracles::ProfileOracle;
 
-type Struct = Vec<(String, String)>;
-
-fn kv_parse(inp: &str) -> Struct {
-    inp.split('&')
-        .map(|kv_str| {
-            let mut parts = kv_str.split('=');
-            let k = parts.next().unwrap();
-            let v = parts.next().unwrap();
-            (String::from(k), String::from(v))
-        })
-        .collect()
-}
-
-fn sanitize(inp: &str) -> String {
-    inp.chars()
-        .filter(|c| *c != '&')
-        .filter(|c| *c != '=')
-        .collect()
-}
-
-fn kv_encode(inp: &Struct) -> String {
-    let mut s = String::new();
-    let mut it = inp.iter();
-    if let Some((k, v)) = it.next() {
-        s.push_str(&format!("{}={}", sanitize(k), sanitize(v)));
-    }
-    for (k, v) in it {
-        s.push_str(&format!("&{}={}", sanitize(k), sanitize(v)));
-    }
-    return s;
-}
-
-fn create_struct(email: &str, uid: &str, role: &str) -> Struct {
-    let uid = String::from(uid);
-    let role = String::from(role);
-    let email = String::from(email);
-    // Order is important. Using same as in challenge description
-    return vec![
-        (String::from("email"), email),
-        (String::from("uid"), uid),
-        (String::from("role"), role),
-    ];
-}
-
-fn profile_for(email: &str) -> String {
-    let stru = create_struct(email, "10", "user");
-    return kv_encode(&stru);
-}
-
-fn encr_for_email(email: &[u8]) -> Vec<u8> {
-    let email = String::from_utf8(Vec::from(email)).expect("Invalid email");
-    let profile = profile_for(&email);
-    return code::ecb(code::blackbox_aes(), profile.as_bytes());
-}
-
-fn decrypt_validate(cipher: &mut [u8]) {
-    // Inplace decrypt without touching padding
-    code::blackbox_aes().ecb_decr(cipher);
-    if let Some(plain) = code::pkcs7_validate(cipher) {
+fn decrypt_validate(oracle: &ProfileOracle, cipher: &[u8]) {
+    if let Some(kvs) = oracle.decrypt_profile(cipher) {
         println!("Padding ok");
-        let plain = String::from_utf8(Vec::from(plain)).expect("Bad utf8");
-        println!("Got plain string [{}]", plain);
-        let kvs = kv_parse(&plain);
         println!("Got key value pairs:");
         for (k,v) in kvs {
             println!("    [{}] = [{}]", k, v);
         }
 
     } else {
-        println!("Bad padding");
+        println!("Bad padding or not a profile");
     }
 }
 
-fn attacker() -> Vec<u8> {
+fn attacker(oracle: &ProfileOracle) -> Vec<u8> {
     // Want first plaintext to be:
     // email=abc12@foo.|com&uid=10&role=|user
     // So email is abc12@foo.com
@@ -87,8 +28,8 @@ fn attacker() -> Vec<u8> {
     // block. |admin|=5, 16-5 = 11 = 0x0b
     let email2 = b"0123456789admin\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b";
 
-    let cipher1 = encr_for_email(email1);
-    let cipher2 = encr_for_email(email2);
+    let cipher1 = oracle.encrypt_profile(email1);
+    let cipher2 = oracle.encrypt_profile(email2);
 
     // First two blocks of cipher1 gives
     // email=abc12@foo.com&uid=10&role=
@@ -104,7 +45,8 @@ fn attacker() -> Vec<u8> {
         .collect();
 }
 
-fn main() {
-    let mut attacker_cipher = attacker();
-    decrypt_validate(&mut attacker_cipher);
+pub fn main() {
+    let oracle = ProfileOracle::new();
+    let attacker_cipher = attacker(&oracle);
+    decrypt_validate(&oracle, &attacker_cipher);
 }
@@ -1,4 +1,6 @@
 use cryptopals::code;
+use code::analysis;
+use code::oracles::{BudgetExceeded, EcbSuffixOracle, Metered};
 
 use lazy_static::*;
 use rayon::prelude::*;
@@ -15,72 +17,65 @@ lazy_static! {
     static ref VICTIM_PLAIN: Vec<u8> = code::decode_base64(VICTIM_B64);
 }
 
-/// AES-128-ECB(random-prefix || attacker-controlled || target-bytes,
-///             random-key)
-fn prepend_ecb(attacker_prefix: &[u8]) -> Vec<u8> {
-    let mut buf: Vec<u8> = rnd_prefix()
-        .into_iter()
-        .chain(attacker_prefix.iter().copied())
-        .chain(VICTIM_PLAIN.iter().copied())
-        .collect();
-    code::blackbox_aes().ecb_encr(&mut buf);
-    return buf;
+/// Max length of the random prefix the oracle prepends
+const MAX_PREFIX: usize = 1023;
+
+/// Enough for the whole attack, which takes around 200k queries
+const QUERY_BUDGET: usize = 1_000_000;
+
+type Oracle<'a> = Metered<'a, Vec<u8>>;
+
+/// Why the attack gave up
+#[derive(Clone, Copy, Debug)]
+enum Failure {
+    Budget(BudgetExceeded),
+    /// No attempt got aligned, so the oracle is probably not ECB
+    NeverAligned { attempts: usize },
 }
 
-fn rnd_prefix() -> Vec<u8> {
-    let count = code::rnd_interval(0, 1023);
-    return code::rnd(count);
+impl From<BudgetExceeded> for Failure {
+    fn from(e: BudgetExceeded) -> Self {
+        Failure::Budget(e)
+    }
 }
 
 /// Tries different prefix lengths multiple times and records all resulting
 /// ciphertext lengths.
-/// Returns the largest power of 2 that divides all lengths.
-fn find_blocksize() -> usize {
+/// Returns the greatest common divisor of all lengths.
+fn find_blocksize(oracle: &Oracle) -> Result<usize, BudgetExceeded> {
     // Number of different prefixes to test
     let prefixes = 100;
     // Number of tests per prefix length
     let samples = 100;
 
     let prefix = vec![0u8; prefixes];
-    let mut lens: Vec<_> = (0..prefixes)
+    let lens: Vec<_> = (0..prefixes)
         .into_par_iter()
         .map(|pref_len| {
             rayon::iter::repeatn(pref_len, samples)
-                .map(|l| prepend_ecb(&prefix[0..l]).len())
+                .map(|l| oracle.query(&prefix[0..l]).map(|c| c.len()))
         })
         .flatten()
-        .collect();
-
-    // Annoying to do true gcd on all of lens.
-    // Just going to guess that it's a power of two and try all
-    lens.sort();
-    lens.dedup();
-    let longest = lens[lens.len() - 1];
-    let mut guess = longest.next_power_of_two();
-    while guess > 1 {
-        // If guess divides all in lens, guess is greatest power of 2
-        // that divides all
-        if lens.iter().all(|l| l % guess == 0) {
-            return guess;
-        } else {
-            guess /= 2;
-        }
-    }
-    return 1;
+        .collect::<Result<_, _>>()?;
+    return Ok(analysis::block_size_gcd(&lens));
 }
 
 /// Finds what the AES([b,b,...,b], hidden_key) is
-fn find_cipherblock_rep(b: u8, blocksize: usize) -> Vec<u8> {
+fn find_cipherblock_rep(
+    oracle: &Oracle,
+    b: u8,
+    blocksize: usize,
+) -> Result<Vec<u8>, BudgetExceeded> {
     // Prepend 3 copies so there must be two full blocks,
     // regardless of random length.
     let prefix = vec![b; 3 * blocksize];
-    let cipher = prepend_ecb(&prefix);
+    let cipher = oracle.query(&prefix)?;
     let blocks: Vec<_> = cipher.chunks_exact(blocksize).collect();
     for i in 1..blocks.len() {
         let prev = blocks[i - 1];
         let this = blocks[i];
         if code::cmp_blocks(prev, this) {
-            return this.into();
+            return Ok(this.into());
         }
     }
     // Can only get here if no two consecutive cipher blocks are identical,
@@ -113,17 +108,28 @@ fn idx_of_first_matches(
 
 const ALIGNMENT_BLOCKS: usize = 10;
 const ALIGNMEN_TRASH: u8 = 1;
+/// Every attempt is aligned with probability 1/blocksize, so failing this
+/// many times in a row means something is broken
+const ALIGNMENT_ATTEMPTS: usize = 1000;
 
 // Runs oracle multiple times until given prefix is block aligned.
-struct Aligner {
+struct Aligner<'a> {
+    oracle: &'a Oracle<'a>,
     blocksize: usize,
     cipher0: Vec<u8>,
 }
 
-impl Aligner {
-    fn new(blocksize: usize) -> Self {
-        let cipher0 = find_cipherblock_rep(0, blocksize);
-        Self { blocksize, cipher0 }
+impl<'a> Aligner<'a> {
+    fn new(
+        oracle: &'a Oracle<'a>,
+        blocksize: usize,
+    ) -> Result<Self, BudgetExceeded> {
+        let cipher0 = find_cipherblock_rep(oracle, 0, blocksize)?;
+        Ok(Self {
+            oracle,
+            blocksize,
+            cipher0,
+        })
     }
 
     /// Returns iterator over new pre_prefix
@@ -139,12 +145,19 @@ impl Aligner {
 
     /// Returns a cipher where `prefix` is block-aligned,
     /// and the block index of the first block of `prefix`
-    fn aligned_cipher(&self, prefix: &[u8]) -> (usize, Vec<u8>) {
-        rayon::iter::repeat(())
-            .filter_map(|_| {
+    fn aligned_cipher(
+        &self,
+        prefix: &[u8],
+    ) -> Result<(usize, Vec<u8>), Failure> {
+        let found = (0..ALIGNMENT_ATTEMPTS)
+            .into_par_iter()
+            .find_map_any(|_| {
                 let prefix_buf: Vec<_> =
                     self.pre_prefix().chain(prefix.iter().copied()).collect();
-                let cipher = prepend_ecb(&prefix_buf);
+                let cipher = match self.oracle.query(&prefix_buf) {
+                    Ok(cipher) => cipher,
+                    Err(e) => return Some(Err(e)),
+                };
                 let idx = idx_of_first_matches(
                     &cipher,
                     &self.cipher0,
@@ -152,10 +165,14 @@ impl Aligner {
                 );
                 // idx is block idx to start of our prefix with
                 // the alignment blocks, but not with trash
-                idx.map(|idx| (idx + ALIGNMENT_BLOCKS, cipher))
-            })
-            .find_any(|_| true)
-            .expect("Somehow ran infinite attempts and all failed")
+                idx.map(|idx| Ok((idx + ALIGNMENT_BLOCKS, cipher)))
+            });
+        match found {
+            Some(res) => Ok(res?),
+            None => Err(Failure::NeverAligned {
+                attempts: ALIGNMENT_ATTEMPTS,
+            }),
+        }
     }
 }
 
@@ -167,24 +184,24 @@ fn spin_block(
     aligner: &Aligner,
     plain_prefix: &[u8],
     target_cipher: &[u8],
-) -> Option<u8> {
+) -> Result<Option<u8>, Failure> {
     assert!(plain_prefix.len() == aligner.blocksize - 1);
     // Exactly one block
     let mut prefix: Vec<_> =
         plain_prefix.iter().copied().chain(once(0)).collect();
     for b in 0..=255 {
         prefix[aligner.blocksize - 1] = b;
-        let (pref_idx, cipher) = aligner.aligned_cipher(&prefix);
+        let (pref_idx, cipher) = aligner.aligned_cipher(&prefix)?;
         let target_block = cipher
             .chunks_exact(aligner.blocksize)
             .skip(pref_idx)
             .next()
             .unwrap();
         if code::cmp_blocks(target_block, target_cipher) {
-            return Some(b);
+            return Ok(Some(b));
         }
     }
-    return None;
+    return Ok(None);
 }
 
 const CRACK_TRASH: u8 = 2u8;
@@ -194,7 +211,10 @@ const CRACK_TRASH: u8 = 2u8;
 // after padding.
 // If plain contains something that is not in the plantext
 // (like a trailing 0x01) this will fail and return None.
-fn crack_next(aligner: &Aligner, plain: &[u8]) -> Option<u8> {
+fn crack_next(
+    aligner: &Aligner,
+    plain: &[u8],
+) -> Result<Option<u8>, Failure> {
     let blocksize = aligner.blocksize;
     // Number of bytes needed to prepend so "target byte" is last byte is a
     // in a plain block.
@@ -203,7 +223,7 @@ fn crack_next(aligner: &Aligner, plain: &[u8]) -> Option<u8> {
     // pref_idx is is the block index in cipher that contains our prefix.
     // Thus the block following it (pref_idx+1) contains the start of the
     // victim plaintext.
-    let (pref_idx, cipher) = aligner.aligned_cipher(&prefix);
+    let (pref_idx, cipher) = aligner.aligned_cipher(&prefix)?;
     // Skipping our prefix then the length of plain means we get the block
     // containing the target byte, which will be last of that block:
     let known_cipher = cipher
@@ -228,11 +248,11 @@ fn crack_next(aligner: &Aligner, plain: &[u8]) -> Option<u8> {
     return spin_block(&aligner, &plain_block, &known_cipher);
 }
 
-fn main() {
-    let bs = find_blocksize();
-    let aligner = Aligner::new(bs);
+fn crack(oracle: &Oracle) -> Result<Vec<u8>, Failure> {
+    let bs = find_blocksize(oracle)?;
+    let aligner = Aligner::new(oracle, bs)?;
     let mut plain_bytes = Vec::new();
-    while let Some(b) = crack_next(&aligner, &plain_bytes) {
+    while let Some(b) = crack_next(&aligner, &plain_bytes)? {
         plain_bytes.push(b);
         println!("{:02x?}", plain_bytes);
         let s = String::from_utf8(plain_bytes.clone());
@@ -241,9 +261,32 @@ fn main() {
         }
         println!("\n");
     }
+    // Last byte is the 0x01 from the padding
+    plain_bytes.pop();
+    Ok(plain_bytes)
+}
 
-    let final_plain_bytes = &plain_bytes[..(plain_bytes.len()-1)];
-    let final_plain_str = String::from_utf8(Vec::from(final_plain_bytes))
-        .unwrap_or_else(|_| String::from("NOT UTF8"));
-    println!("Final answer\n{:02x?}\n{}", final_plain_bytes, final_plain_str);
+pub fn main() {
+    let victim =
+        EcbSuffixOracle::new(&VICTIM_PLAIN).with_random_prefix(MAX_PREFIX);
+    let oracle = Metered::new(|x| victim.query(x)).with_budget(QUERY_BUDGET);
+    match crack(&oracle) {
+        Ok(plain) => {
+            let plain_str = String::from_utf8(plain.clone())
+                .unwrap_or_else(|_| String::from("NOT UTF8"));
+            println!("Final answer\n{:02x?}\n{}", plain, plain_str);
+        }
+        Err(Failure::Budget(e)) => {
+            println!("Gave up after the budget of {} queries", e.budget)
+        }
+        Err(Failure::NeverAligned { attempts }) => println!(
+            "Gave up, not aligned in {} attempts, is the oracle ECB?",
+            attempts
+        ),
+    }
+    let stats = oracle.stats();
+    println!(
+        "Used {} queries, {} bytes, in {:?}",
+        stats.queries, stats.bytes, stats.time
+    );
 }
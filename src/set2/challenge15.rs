@@ -11,7 +11,7 @@ fn testit(inp: &[u8]) {
     }
 }
 
-fn main() {
+pub fn main() {
     let inp1 = b"ICE ICE BABY\x04\x04\x04\x04";
     let inp2 = b"ICE ICE BABY\x05\x05\x05\x05";
     let inp3 = b"ICE ICE BABY\x01\x02\x03\x04";
@@ -1,42 +1,9 @@
 use cryptopals::code;
+use code::display::View;
+use code::oracles::{cbc_bitflip::PREFIX, CbcBitflipOracle};
 
 use std::iter::repeat;
 
-fn sanitize_userdata(userdata: &str) -> String {
-    userdata
-        .chars()
-        .filter(|c| *c != ';')
-        .filter(|c| *c != '=')
-        .collect()
-}
-
-const PREFIX: &str = "comment1=cooking%20MCs;userdata=";
-const SUFFIX: &str = ";comment2=%20like%20a%20pound%20of%20bacon";
-
-/// The first function described.
-/// Prepends `PREFIX` and appends `SUFFIX`,
-/// then encrypts using the hidden key in CBC with random iv.
-/// Returns the used iv and resulting cipher text
-fn encrypt(userdata: &str) -> (Vec<u8>, Vec<u8>) {
-    let sanitized = sanitize_userdata(userdata).into_bytes().into_iter();
-    let mut buf: Vec<_> = PREFIX
-        .bytes()
-        .chain(sanitized)
-        .chain(SUFFIX.bytes())
-        .collect();
-    let iv = code::rnd(code::aes128::BLOCKSIZE);
-    code::blackbox_aes().cbc_encr(&iv, &mut buf);
-    return (iv, buf);
-}
-
-/// Second function described.
-/// Doesn't parse into string.
-fn decrypt(iv: &[u8], cipher: &[u8]) -> Vec<u8> {
-    let mut buf = Vec::from(cipher);
-    code::blackbox_aes().cbc_decr(iv, &mut buf);
-    return buf;
-}
-
 struct Userdata {
     /// The user data to supply the encryption function with
     data: String,
@@ -80,10 +47,12 @@ fn construct_userdata() -> Userdata {
     };
 }
 
-fn main() {
+pub fn main() {
+    let oracle = CbcBitflipOracle::new();
     let bs = code::aes128::BLOCKSIZE;
     let userdata = construct_userdata();
-    let (iv, mut cipher) = encrypt(&userdata.data);
+    let (iv, mut cipher) = oracle.encrypt(&userdata.data);
+    let before = oracle.decrypt(&iv, &cipher);
 
     let prevblock = cipher
         .chunks_exact_mut(bs)
@@ -94,10 +63,13 @@ fn main() {
         prevblock[i as usize] ^= 0x1; // Flip least sig bit
     }
 
-    let decr = decrypt(&iv, &cipher);
+    let decr = oracle.decrypt(&iv, &cipher);
+    println!("Before and after flipping:");
+    print!("{}", View::new(bs).diff(&before, &decr));
     let decr = code::pkcs7_validate(&decr).expect("Bad padding");
 
     println!("decr = {:?}", decr);
     let decr_str = String::from_utf8_lossy(&decr);
     println!("As text:\n[{}]", decr_str);
+    println!("Admin: {}", oracle.is_admin(&iv, &cipher));
 }
@@ -1,6 +1,6 @@
 use cryptopals::code;
 
-fn main() {
+pub fn main() {
     let inp = "YELLOW SUBMARINE";
     let mut buf = Vec::from(inp.as_bytes());
     code::pkcs7_pad(&mut buf, 5);
@@ -1,5 +1,6 @@
 
 use cryptopals::code;
+use code::oracles::CbcPaddingOracle;
 
 use std::iter::once;
 
@@ -16,47 +17,27 @@ static STRS: &[&str] = &[
     "MDAwMDA5aXRoIG15IHJhZy10b3AgZG93biBzbyBteSBoYWlyIGNhbiBibG93"
 ];
 
-fn random_inp() -> Vec<u8> {
-    let i = code::rnd_interval(0, STRS.len()-1);
-    let b64 = STRS[i];
-    let bytes = code::decode_base64(b64);
-    let s = String::from_utf8(bytes.clone()).unwrap();
-    println!("Using random input\n{} =\n{}\n", b64, s);
-    return bytes;
+fn secrets() -> Vec<Vec<u8>> {
+    STRS.iter().map(|b64| code::decode_base64(b64)).collect()
 }
 
-// Assuming we should generate random iv
-
-/// Encrypts a random string with an unknown key
-/// Uses random IV
-/// Returns (iv,cipher)
-fn random_cbc() -> (Vec<u8>, Vec<u8>) {
-    let iv = code::rnd(code::aes128::BLOCKSIZE);
-    let plain = random_inp();
-    let mut buf = Vec::from(plain);
-    code::blackbox_aes().cbc_encr(&iv, &mut buf);
-    return (iv, buf);
-}
-
-fn check_cbc_padding(iv: &[u8], cipher: &[u8]) -> bool {
-    let mut buf = Vec::from(cipher);
-    code::blackbox_aes().cbc_decr(iv, &mut buf);
-    return code::pkcs7_validate(&buf).is_some();
-}
-
-fn crack_block(prev: &[u8], this: &[u8]) -> Vec<u8> {
-    let mut buf = decrypt_block(this);
+fn crack_block(oracle: &CbcPaddingOracle, prev: &[u8], this: &[u8])
+    -> Vec<u8>
+{
+    let mut buf = decrypt_block(oracle, this);
     code::block_xor(&mut buf, prev);
     return buf;
 }
 
-fn crack_cbc(iv: &[u8], ciphertext: &[u8]) -> Vec<u8> {
+fn crack_cbc(oracle: &CbcPaddingOracle, iv: &[u8], ciphertext: &[u8])
+    -> Vec<u8>
+{
     let blocksize = code::aes128::BLOCKSIZE;
     let cipherblocks = ciphertext.chunks_exact(blocksize);
     let with_iv = once(iv).chain(ciphertext.chunks_exact(blocksize));
     let mut buf = Vec::with_capacity(ciphertext.len());
     with_iv.zip(cipherblocks)
-        .map(|(prev,this)| crack_block(prev, this))
+        .map(|(prev,this)| crack_block(oracle, prev, this))
         .for_each(|v| buf.extend(v));
     return buf;
 }
@@ -64,7 +45,9 @@ fn crack_cbc(iv: &[u8], ciphertext: &[u8]) -> Vec<u8> {
 /// Cracks what AES_DECRYPT(KEY, `cipherblock`) is.
 /// So does _not_ return final plaintext since
 /// it is not XOR:ed with previous ciphertext.
-fn decrypt_block(cipherblock: &[u8]) -> Vec<u8> {
+fn decrypt_block(oracle: &CbcPaddingOracle, cipherblock: &[u8])
+    -> Vec<u8>
+{
     let blocksize = cipherblock.len();
     let mut decrblock = vec![0; blocksize];
     let mut ivp = vec![0;blocksize];
@@ -74,7 +57,7 @@ fn decrypt_block(cipherblock: &[u8]) -> Vec<u8> {
     // last byte is 0x01 is decrypted block. But could also be eg 0x02, 0x02.
     for b in 0..=255 {
         ivp[blocksize - 1] = b;
-        if check_cbc_padding(&ivp, cipherblock) {
+        if oracle.check_padding(&ivp, cipherblock) {
             // This is _not_ certainly a hit
             // Say last byte of decrypted block is x
             // All we know is that 1 <= x <= blocksize
@@ -85,7 +68,7 @@ fn decrypt_block(cipherblock: &[u8]) -> Vec<u8> {
             // BUT, if x=1, then chaning second last byte won't change
             // padding to not valid.
             ivp[blocksize-2] ^= 1; // Flip a bit to make it different
-            if check_cbc_padding(&ivp, cipherblock) {
+            if oracle.check_padding(&ivp, cipherblock) {
                 // Ok, now we can be certain that last byte is 0x01
                 decrblock[blocksize-1] = b ^ 0x01;
             } else {
@@ -108,7 +91,7 @@ fn decrypt_block(cipherblock: &[u8]) -> Vec<u8> {
         // Then cycle over ivp[i] until correct padding
         for b in 0..=255 {
             ivp[i] = b;
-            if check_cbc_padding(&ivp, cipherblock) {
+            if oracle.check_padding(&ivp, cipherblock) {
                 // We know that last of decr is padbyte
                 // Valid padding -> last padbyte blocks all equal padbyte
                 // Since padbyte=blocksize-i, decr[i] = padbyte
@@ -122,12 +105,14 @@ fn decrypt_block(cipherblock: &[u8]) -> Vec<u8> {
     return decrblock;
 }
 
-fn main() {
-    let (iv, cipher) = random_cbc();
-    
-    let plain = crack_cbc(&iv, &cipher);
+pub fn main() {
+    let oracle = CbcPaddingOracle::new(secrets());
+    let (iv, cipher) = oracle.encrypt_secret();
+
+    let plain = crack_cbc(&oracle, &iv, &cipher);
     println!("Plain before unpad:\n{:02x?}", plain);
     let plain = code::pkcs7_validate(&plain).expect("Bad padding?");
     let s = String::from_utf8(Vec::from(plain)).unwrap();
     println!("After removing padding\n{:02x?}\nstring = [{}]", plain , s);
+    println!("Padding queries: {}", oracle.queries());
 }
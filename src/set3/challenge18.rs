@@ -1,7 +1,7 @@
 
 use cryptopals::code;
 
-fn main() {
+pub fn main() {
     let nonce = 0u64;
 
     let key = "YELLOW SUBMARINE";
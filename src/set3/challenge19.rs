@@ -1,40 +1,24 @@
 use cryptopals::code;
+use code::encoding::hexdump::printable;
+use code::oracles::CtrFixedNonceOracle;
 
 use std::iter::{once, repeat};
 
-fn ctr_fixed(inp: &mut [u8]) {
-    let nonce = 0u64;
-    code::blackbox_aes().ctr_inplace(nonce, inp);
-}
-
-fn ciphertexts() -> Vec<Vec<u8>> {
+fn ciphertexts(oracle: &CtrFixedNonceOracle) -> Vec<Vec<u8>> {
     let f = code::load_str("challenge-data/19.txt");
     f.lines()
         .map(|b64| code::decode_base64(b64))
-        .map(|mut buf| {
-            ctr_fixed(&mut buf);
-            buf
-        })
+        .map(|buf| oracle.encrypt(&buf))
         .collect()
 }
 
-/// If normal printable ascii, return ascii char.
-/// Else . (like xxd)
-fn pretty_char(b: u8) -> char {
-    if b >= 0x20 && b <= 0x7e {
-        b.into()
-    } else {
-        '.'
-    }
-}
-
 /// Show what decrypted part looks like in ascii, followed by remaining
 /// in hex
 fn showline(keystream: &[u8], cipher: &[u8]) {
     cipher
         .iter()
         .zip(keystream)
-        .map(|(x, k)| pretty_char(*k ^ *x))
+        .map(|(x, k)| printable(*k ^ *x))
         .for_each(|c| print!("{}", c));
     println!("");
 }
@@ -128,7 +112,8 @@ fn session(ciphers: Vec<Vec<u8>>) {
     }
 }
 
-fn main() {
-    let ciphers = ciphertexts();
+pub fn main() {
+    let oracle = CtrFixedNonceOracle::new();
+    let ciphers = ciphertexts(&oracle);
     session(ciphers);
 }
@@ -1,29 +1,22 @@
 use cryptopals::code;
+use code::oracles::CtrFixedNonceOracle;
 
 use rayon::prelude::*;
 
-fn ctr_fixed(inp: &mut [u8]) {
-    let nonce = 0u64;
-    code::blackbox_aes().ctr_inplace(nonce, inp);
-}
-
-fn ciphertexts() -> Vec<Vec<u8>> {
+fn ciphertexts(oracle: &CtrFixedNonceOracle) -> Vec<Vec<u8>> {
     let f = code::load_str("challenge-data/20.txt");
     f.lines()
         .map(|b64| code::decode_base64(b64))
-        .map(|mut buf| {
-            ctr_fixed(&mut buf);
-            buf
-        })
+        .map(|buf| oracle.encrypt(&buf))
         .collect()
 }
 
 /// Gets real keystream to compare against
-fn get_real_keystream(len: usize) -> Vec<u8> {
+fn get_real_keystream(oracle: &CtrFixedNonceOracle, len: usize)
+    -> Vec<u8>
+{
     // Encrypt all 0s -> get keystream
-    let mut buf = vec![0; len];
-    ctr_fixed(&mut buf);
-    return buf;
+    oracle.encrypt(&vec![0; len])
 }
 
 /// All ciphers must be same length and encrypted with same keystream
@@ -47,14 +40,15 @@ fn crack_keystream(ciphers: &[&[u8]]) -> Vec<u8> {
         .collect()
 }
 
-fn main() {
-    let ciphers = ciphertexts();
+pub fn main() {
+    let oracle = CtrFixedNonceOracle::new();
+    let ciphers = ciphertexts(&oracle);
 
     // Truncate all to smallest length
     let minlen = ciphers.iter().map(|c| c.len()).min().expect("No ciphers?");
     let ciphers: Vec<_> = ciphers.iter().map(|c| &c[0..minlen]).collect();
 
-    let real_keystream = get_real_keystream(minlen);
+    let real_keystream = get_real_keystream(&oracle, minlen);
     let keystream = crack_keystream(&ciphers);
 
     println!("\n\n");
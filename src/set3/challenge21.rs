@@ -1,7 +1,7 @@
 
 use cryptopals::code::MT19937;
 
-fn main() {
+pub fn main() {
     let n: Vec<_> = MT19937::default().take(10).collect();
     println!("{:?}", n);
 }
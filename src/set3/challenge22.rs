@@ -1,29 +1,40 @@
-use cryptopals::code::{self,Clock};
+use cryptopals::code;
+use code::time::{Jitter, SimClock, TimeSource};
 
 /// The described routine
 /// Returns the generated value and the used seed
-fn routine(clock: &mut Clock) -> (u32, u32) {
-    let prewait = code::rnd_interval(40, 1000) as u32;
-    clock.wait(prewait);
+fn routine(clock: &dyn TimeSource, jitter: &mut Jitter) -> (u32, u32) {
+    jitter.wait_ms(clock, 40_000, 1_000_000);
     let seed = clock.timestamp();
     let mut rng = code::MT19937::new(seed);
-    let postwait = code::rnd_interval(40, 1000) as u32;
-    clock.wait(postwait);
+    jitter.wait_ms(clock, 40_000, 1_000_000);
     return (rng.get_next(), seed);
 }
 
-fn main() {
+pub const DEMOS: &[(&str, fn())] = &[("whole-space", demo_whole_space)];
+
+pub fn main() {
     // TODO: Just gonna bruteforce, supposed to do other way?
-    let mut clock = Clock::now();
+    let clock = SimClock::seeded();
+    let mut jitter = Jitter::new(code::rng::random());
     let start = clock.timestamp();
-    let (target, real_seed) = routine(&mut clock);
-    let guess_seed = (start..)
-        .skip_while(|seed| {
-            let mut rng = code::MT19937::new(*seed);
-            let x = rng.get_next();
-            x != target
-        })
-        .next()
-        .unwrap();
+    let (target, real_seed) = routine(&clock, &mut jitter);
+    let guess_seed =
+        code::mt19937::find_seed(start..=clock.timestamp(), &[target])
+            .unwrap();
     println!("Real seed is {}\nGuess is {}", real_seed, guess_seed);
+
+    // With the entire first block of outputs, no bruteforce is needed
+    let outputs: Vec<_> = code::MT19937::new(real_seed).take(624).collect();
+    let recovered = code::mt19937::recover_seed_from_outputs(&outputs);
+    println!("Recovered from 624 outputs: {:?}", recovered);
+}
+
+/// Any seed at all, not just a recent timestamp.
+/// OBS: takes minutes, run with --release
+fn demo_whole_space() {
+    let real_seed: u32 = code::rng::random();
+    let target = code::MT19937::new(real_seed).get_next();
+    let guess_seed = code::mt19937::find_seed(0..=u32::max_value(), &[target]);
+    println!("Real seed is {}\nGuess is {:?}", real_seed, guess_seed);
 }
@@ -1,18 +1,60 @@
-
 use cryptopals::code;
-use rand;
+use code::mt19937_solver::{Observation, SolveError, Solver};
 
-fn crack_state<I>(it: I) -> code::MT19937
+pub fn crack_state<I>(it: I) -> code::MT19937
 where I: Iterator<Item=u32>
 {
     let v: Vec<_> = it.take(624).map(code::mt19937::untemper).collect();
     return code::MT19937::from_state(&v, 0);
 }
 
-fn main() {
-    let old = code::MT19937::new(rand::random());
+/// Like `crack_state` but only sees the top `bits` bits of every `step`th
+/// output. Twice 19968/bits observations is plenty to pin the state down.
+fn crack_state_truncated<I>(it: I, bits: usize, step: usize) -> code::MT19937
+where I: Iterator<Item=u32>
+{
+    let needed = 2 * 19968 / bits;
+    let mut solver = Solver::new();
+    it.step_by(step)
+        .take(needed)
+        .enumerate()
+        .for_each(|(i, x)| {
+            let top = x >> (32 - bits);
+            solver.observe(Observation::top_bits(i * step, top, bits))
+        });
+    match solver.solve() {
+        Ok(rng) => rng,
+        // Lower bits of the first word only ever show up in the first output
+        Err(SolveError::Underdetermined { free_bits, guess })
+            if free_bits <= 31 =>
+        {
+            println!("{} bits unknown, first output might be wrong", free_bits);
+            *guess
+        }
+        Err(e) => panic!("Could not solve for state: {:?}", e),
+    }
+}
+
+fn demo_full() {
+    let old = code::MT19937::new(code::rng::random());
     let new = crack_state(old.clone());
     let old_nums: Vec<_> = old.take(10).collect();
     let new_nums: Vec<_> = new.take(10).collect();
     println!("{:?}\n{:?}", old_nums, new_nums);
 }
+
+/// OBS: slow, run with --release
+fn demo_truncated() {
+    let old = code::MT19937::new(code::rng::random());
+    // Only every other output, and only 8 bits of those
+    let new = crack_state_truncated(old.clone(), 8, 2);
+    let old_nums: Vec<_> = old.skip(1).take(10).collect();
+    let new_nums: Vec<_> = new.skip(1).take(10).collect();
+    println!("{:?}\n{:?}", old_nums, new_nums);
+}
+
+pub const DEMOS: &[(&str, fn())] = &[("truncated", demo_truncated)];
+
+pub fn main() {
+    demo_full();
+}
@@ -1,26 +1,12 @@
-use cryptopals::code::{self, Clock};
-
-use rand;
-use rayon::prelude::*;
-use std::iter::once;
-
-fn bytes_of_word(w: u32) -> impl Iterator<Item = u8> {
-    let mask = 0x000000ffu32;
-    let a = ((w >> 0) & mask) as u8;
-    let b = ((w >> 8) & mask) as u8;
-    let c = ((w >> 16) & mask) as u8;
-    let d = ((w >> 24) & mask) as u8;
-    once(a).chain(once(b)).chain(once(c)).chain(once(d))
-}
+use cryptopals::code;
+use code::time::{Jitter, SimClock, TimeSource};
+use code::prng_cipher::{recovery, LittleEndian, PrngStreamCipher};
 
-fn keystream(key: u16) -> impl Iterator<Item = u8> {
-    code::MT19937::new(key as u32).map(bytes_of_word).flatten()
-}
+
+type MtCipher = PrngStreamCipher<code::MT19937, LittleEndian>;
 
 fn encrypt(key: u16, inp: &mut [u8]) {
-    inp.iter_mut()
-        .zip(keystream(key))
-        .for_each(|(x, k)| *x ^= k)
+    MtCipher::new(key as u32).apply(inp);
 }
 
 fn encr_and_show(key: u16, inp: &mut [u8]) {
@@ -50,82 +36,91 @@ fn prefix_encr() -> Vec<u8> {
     let rnds = code::rnd(rndlen);
     let mut buf = Vec::from(rnds);
     buf.extend(KNOWN_PLAINTEXT);
-    let key = rand::random();
+    let key = code::rng::random();
     println!("Real key = {}", key);
     encrypt(key, &mut buf);
     return buf;
 }
 
+/// Known keystream from KNOWN_PLAINTEXT at the end of `cipher`
+fn known_keystream(cipher: &[u8]) -> Vec<recovery::KnownByte> {
+    let offset = cipher.len() - KNOWN_PLAINTEXT.len();
+    recovery::known_keystream(cipher, offset, KNOWN_PLAINTEXT)
+}
+
 fn recover_key(cipher: &[u8]) -> u16 {
     // TODO: Just gonna bruteforce 16-bits, ok?
-    let target_keystream = {
-        // The encrypted KNOWN_PLAINTEXT
-        let mut buf = Vec::from(code::lastn(cipher, KNOWN_PLAINTEXT.len()));
-        // xor with plain to get keytream
-        code::block_xor(&mut buf, KNOWN_PLAINTEXT);
-        buf
-    };
-    let to_skip = cipher.len() - target_keystream.len();
-    // Try all possible keys until get right section of keystream
-    (0..=u16::max_value())
-        .into_par_iter()
-        .filter(|k| {
-            keystream(*k)
-                .skip(to_skip)
-                .zip(target_keystream.iter())
-                .all(|(a, b)| a == *b)
-        })
-        .find_any(|_| true)
-        .expect("No u16 gave matching keystream")
+    let known = known_keystream(cipher);
+    let seeds = 0..=(u16::max_value() as u32);
+    recovery::recover_seed::<code::MT19937, LittleEndian>(&known, seeds)
+        .expect("No u16 gave matching keystream") as u16
+}
+
+/// Like `recover_key` but for any 32-bit key.
+/// OBS: takes minutes, run with --release
+fn recover_key32(cipher: &[u8]) -> u32 {
+    let known = known_keystream(cipher);
+    recovery::recover_mt_seed::<LittleEndian>(&known, 0..=u32::max_value())
+        .expect("No u32 gave matching keystream")
 }
 
 fn token_from_seed(seed: u32) -> String {
-    let len = 12;
-    let bytes: Vec<u8> = code::MT19937::new(seed)
-        .take(len)
-        .map(bytes_of_word)
-        .flatten()
-        .collect();
+    let len = 4 * 12;
+    let bytes: Vec<u8> = MtCipher::new(seed).keystream().take(len).collect();
     return code::encode_base64(&bytes);
 }
 
-fn new_token(clock: &mut Clock) -> String {
-    clock.waitsome();
+fn new_token(clock: &dyn TimeSource, jitter: &mut Jitter) -> String {
+    jitter.waitsome(clock);
     let seed = clock.timestamp(); // + 200;
     println!("Creating new token using seed {}", seed);
     return token_from_seed(seed);
 }
 
 /// Checks if token was created using system time within the last minute
-fn check_token(clock: &Clock, token: &str) {
+fn check_token(clock: &dyn TimeSource, token: &str) {
     let beg = clock.timestamp();
     let end = beg + 60;
-    match (beg..end)
-        .into_par_iter()
-        .map(|time| (time, token_from_seed(time)))
-        .find_any(|(_, tok)| tok == token)
-    {
-        Some((time, _)) => println!("Time {} was used as seed", time),
+    let words: Vec<u32> = code::decode_base64(token)
+        .chunks_exact(4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect();
+    match code::mt19937::find_seed(beg..=end, &words) {
+        Some(time) => println!("Time {} was used as seed", time),
         None => println!("No recent time was used as seed"),
     }
 }
 
-#[allow(dead_code)]
 fn demo_recover_key() {
     let cipher = prefix_encr();
     let k = recover_key(&cipher);
     println!("Recoved {}", k);
 }
 
-#[allow(dead_code)]
+fn demo_recover_key32() {
+    let rndlen = code::rnd_interval(8, 127);
+    let mut buf = code::rnd(rndlen);
+    buf.extend(KNOWN_PLAINTEXT);
+    let key: u32 = code::rng::random();
+    println!("Real key = {}", key);
+    MtCipher::new(key).apply(&mut buf);
+    let k = recover_key32(&buf);
+    println!("Recoved {}", k);
+}
+
 fn demo_token() {
-    let mut clock = Clock::now();
-    let token = new_token(&mut clock);
+    let clock = SimClock::seeded();
+    let mut jitter = Jitter::new(code::rng::random());
+    let token = new_token(&clock, &mut jitter);
     println!("Token: {}", token);
     check_token(&clock, &token);
 }
 
-fn main() {
-    //demo_recover_key();
+pub const DEMOS: &[(&str, fn())] = &[
+    ("recover-key", demo_recover_key),
+    ("recover-key32", demo_recover_key32),
+];
+
+pub fn main() {
     demo_token();
 }
@@ -0,0 +1,8 @@
+pub mod challenge17;
+pub mod challenge18;
+pub mod challenge19;
+pub mod challenge20;
+pub mod challenge21;
+pub mod challenge22;
+pub mod challenge23;
+pub mod challenge24;
@@ -0,0 +1,24 @@
+use cryptopals::code;
+use code::encoding::hexdump;
+use code::hash::{self, sha1::Sha1Core};
+use code::oracles::MacCookieOracle;
+
+pub fn main() {
+    let oracle = MacCookieOracle::new();
+    let (cookie, mac) = oracle.issue("foo");
+    println!("Cookie: {}", String::from_utf8_lossy(&cookie));
+    println!("MAC:    {}", code::encode_hex(&mac));
+
+    // Only the right key length gives a MAC that verifies
+    let verify = |m: &[u8], t: &[u8]| oracle.verify(m, t).is_some();
+    let suffix = b";admin=true";
+    let forged = hash::length_extension::<Sha1Core, _>(
+        &cookie, &mac, suffix, 64, verify,
+    )
+    .expect("No key length up to 64 worked");
+
+    println!("Key is {} bytes, forged cookie:", forged.keylen);
+    print!("{}", hexdump::encode(&forged.message));
+    println!("MAC:    {}", code::encode_hex(&forged.mac));
+    println!("Admin:  {}", oracle.is_admin(&forged.message, &forged.mac));
+}
@@ -0,0 +1,15 @@
+use cryptopals::code;
+use code::dh::{self, Group, KeyPair};
+
+fn exchange(name: &str, group: &Group) {
+    let (alice, bob) = (KeyPair::new(group), KeyPair::new(group));
+    let s = alice.shared(group, bob.public());
+    assert_eq!(s, bob.shared(group, alice.public()));
+    println!("{}: shared secret {:x}", name, s);
+    println!("    session key {}", code::encode_hex(&dh::session_key(&s)));
+}
+
+pub fn main() {
+    exchange("p = 37, g = 5", &Group::toy());
+    exchange("RFC 3526 1536 bit group", &Group::default());
+}
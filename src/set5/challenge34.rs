@@ -0,0 +1,68 @@
+use cryptopals::code;
+use code::attacks::dh_mitm::{Attack, DhMitm};
+use code::bigint::BigUint;
+use code::dh::Group;
+use code::protocol::dh_echo::{client, echo_bot, Handshake, Msg};
+use code::protocol::{self, Entry, Mitm, Passive};
+
+/// Runs the echo protocol through `mitm` and prints what went by
+pub fn show_run(mitm: &mut dyn Mitm<Msg>, handshake: Handshake) {
+    let group = Group::default();
+    let messages: &[&[u8]] = &[b"Hi bot", b"Ice ice baby"];
+    let client = |ep| client(ep, &group, handshake, messages);
+    let (echoes, _, log) = protocol::run(client, echo_bot, mitm);
+    for Entry {
+        from,
+        sent,
+        delivered,
+    } in &log
+    {
+        println!("{:?}: {}", from, summary(sent));
+        if delivered.as_slice() != [sent.clone()] {
+            for msg in delivered {
+                println!("    became {}", summary(msg));
+            }
+        }
+    }
+    match echoes {
+        Ok(echoes) => println!("Client got {} echoes back", echoes.len()),
+        Err(e) => println!("Client failed: {}", e),
+    }
+}
+
+/// At most 16 hex digits
+fn short(x: &BigUint) -> String {
+    let hex = x.to_hex();
+    match hex.get(..16) {
+        Some(start) if hex.len() > 16 => format!("{}..", start),
+        _ => hex,
+    }
+}
+
+fn summary(msg: &Msg) -> String {
+    match msg {
+        Msg::Hello { group, public } => {
+            format!("Hello g={} A={}", short(&group.g), short(public))
+        }
+        Msg::Negotiate(group) => format!("Negotiate g={}", short(&group.g)),
+        Msg::Ack(group) => format!("Ack g={}", short(&group.g)),
+        Msg::Public(public) => format!("Public {}", short(public)),
+        Msg::Data(cipher) => format!("Data {} bytes", cipher.len()),
+    }
+}
+
+pub fn show_recovered(mitm: &DhMitm) {
+    for (from, plain) in &mitm.recovered {
+        println!("Read from {:?}: {:?}", from, String::from_utf8_lossy(plain));
+    }
+}
+
+pub fn main() {
+    println!("Without a MITM:");
+    show_run(&mut Passive, Handshake::Combined);
+
+    println!("\nWith A and B replaced by p:");
+    let mut mitm = DhMitm::new(Attack::KeyFixing);
+    show_run(&mut mitm, Handshake::Combined);
+    show_recovered(&mitm);
+}
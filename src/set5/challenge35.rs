@@ -0,0 +1,22 @@
+use cryptopals::code;
+use code::attacks::dh_mitm::{Attack, DhMitm};
+use code::protocol::dh_echo::Handshake;
+
+use super::challenge34::{show_recovered, show_run};
+
+pub fn main() {
+    let attacks = [
+        ("g = 1", Attack::GOne),
+        ("g = p", Attack::GP),
+        ("g = p - 1", Attack::GPMinusOne),
+    ];
+    for (i, (name, attack)) in attacks.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        println!("With {} negotiated:", name);
+        let mut mitm = DhMitm::new(*attack);
+        show_run(&mut mitm, Handshake::Negotiated);
+        show_recovered(&mitm);
+    }
+}
@@ -0,0 +1,22 @@
+use cryptopals::code;
+use code::dh::Group;
+use code::protocol::srp::{client, Server, Variant};
+use code::protocol::{self, Passive};
+
+pub fn main() {
+    let mut server = Server::new(Group::default(), Variant::Srp6a);
+    server.register("alice@example.com", "hunter2");
+    let group = server.group().clone();
+    for password in &["hunter2", "hunter3"] {
+        let login = |ep| {
+            client(ep, &group, Variant::Srp6a, "alice@example.com", password)
+        };
+        let (res, _, log) =
+            protocol::run(login, |ep| server.serve(ep), &mut Passive);
+        match res {
+            Ok(()) => println!("{:?}: logged in", password),
+            Err(e) => println!("{:?}: {}", password, e),
+        }
+        println!("    {} messages", log.len());
+    }
+}
@@ -0,0 +1,27 @@
+use cryptopals::code;
+use code::attacks::srp::zero_key_login;
+use code::dh::Group;
+use code::protocol::srp::{Server, Variant};
+use code::protocol::{self, Passive};
+
+pub fn main() {
+    let group = Group::default();
+    let mut server =
+        Server::new(group.clone(), Variant::Srp6a).with_public_check(false);
+    server.register("alice@example.com", &code::encode_hex(&code::rnd(16)));
+    let mut strict = Server::new(group.clone(), Variant::Srp6a);
+    strict.register("alice@example.com", "whatever");
+
+    for multiple in 0..3 {
+        let attack =
+            |ep| zero_key_login(ep, &group, "alice@example.com", multiple);
+        let (res, _, _) =
+            protocol::run(attack, |ep| server.serve(ep), &mut Passive);
+        let (strict_res, _, _) =
+            protocol::run(attack, |ep| strict.serve(ep), &mut Passive);
+        println!(
+            "A = {}N: {:?}, against a server that checks A: {:?}",
+            multiple, res, strict_res
+        );
+    }
+}
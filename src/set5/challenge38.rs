@@ -0,0 +1,36 @@
+use cryptopals::code;
+use code::attacks::srp::{crack, fake_server};
+use code::dh::Group;
+use code::protocol::srp::{client, Variant};
+use code::protocol::{self, Passive};
+
+/// Some of the most common passwords
+const DICTIONARY: &[&str] = &[
+    "123456", "password", "12345678", "qwerty", "123456789", "12345",
+    "1234", "111111", "1234567", "dragon", "123123", "baseball", "abc123",
+    "football", "monkey", "letmein", "696969", "shadow", "master", "666666",
+    "qwertyuiop", "123321", "mustang", "1234567890", "michael", "654321",
+    "superman", "1qaz2wsx", "7777777", "121212", "000000", "qazwsx",
+    "123qwe", "killer", "trustno1", "jordan", "jennifer", "zxcvbnm",
+    "asdfgh", "hunter", "buster", "soccer", "harley", "batman", "andrew",
+    "tigger", "sunshine", "iloveyou", "2000", "charlie", "robert", "thomas",
+];
+
+pub fn main() {
+    let group = Group::default();
+    let password = DICTIONARY[code::rnd_interval(0, DICTIONARY.len() - 1)];
+    let login =
+        |ep| client(ep, &group, Variant::Simplified, "bob", password);
+    let (res, captured, _) =
+        protocol::run(login, |ep| fake_server(ep, &group), &mut Passive);
+    println!("Client thinks the login went: {:?}", res);
+
+    let captured = captured.expect("Client didn't finish the login");
+    println!("Captured proof {}", code::encode_hex(&captured.proof));
+    let cracked = crack(&group, &captured, DICTIONARY.iter().cloned());
+    match cracked {
+        Some(found) => println!("Password: {:?}", found),
+        None => println!("Not in the dictionary"),
+    }
+    assert_eq!(cracked, Some(password));
+}
@@ -0,0 +1,6 @@
+pub mod challenge33;
+pub mod challenge34;
+pub mod challenge35;
+pub mod challenge36;
+pub mod challenge37;
+pub mod challenge38;